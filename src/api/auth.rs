@@ -66,6 +66,14 @@ pub fn get_client_timestamp() -> i64 {
     now + offset
 }
 
+/// Get the current time in milliseconds since the Unix epoch.
+pub fn current_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
+
 /// Check if device ID has expired (older than 180 minutes).
 pub fn is_device_id_expired(timestamp: Option<i64>) -> bool {
     let Some(timestamp) = timestamp else {
@@ -2,14 +2,28 @@
 //!
 //! This module provides:
 //! - HTTP client for Fansly REST API
-//! - WebSocket session management
+//! - WebSocket session management (kept alive in the background) and live events
 //! - Authentication and request signing
+//! - Global rate limiting and bandwidth throttling
+//! - HTTP, HTTPS and SOCKS5 proxy support
 //! - API response types
 
 pub mod auth;
 pub mod client;
+pub mod proxy;
+pub mod ratelimit;
+#[cfg(test)]
+pub(crate) mod stub;
+pub mod throttle;
 pub mod types;
 pub mod websocket;
 
-pub use client::{FanslyApi, BATCH_SIZE};
+pub use client::{
+    FanslyApi, FanslyApiBuilder, BATCH_SIZE, DEFAULT_API_REQUESTS_PER_SECOND,
+    DEFAULT_CDN_REQUESTS_PER_SECOND, DEFAULT_CHECK_KEY, DEFAULT_USER_AGENT,
+};
+pub use proxy::ProxyConfig;
+pub use ratelimit::{RateLimitMetrics, RateLimiter};
+pub use throttle::{BandwidthLimiter, BandwidthSchedule};
 pub use types::*;
+pub use websocket::{SessionKeeper, WsEvent};
@@ -0,0 +1,189 @@
+//! Token-bucket rate limiting shared by all API and CDN requests.
+//!
+//! Each request takes one token. Tokens refill at a fixed rate up to a burst
+//! capacity. When the bucket is empty the balance goes negative, which queues
+//! concurrent callers in order: each one waits until its own token would have
+//! been refilled.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tokio::time::sleep;
+
+/// Token-bucket rate limiter.
+#[derive(Debug)]
+pub struct RateLimiter {
+    name: &'static str,
+    /// Tokens refilled per second; `None` disables limiting.
+    rate: Option<f64>,
+    burst: f64,
+    bucket: Mutex<Bucket>,
+    requests: AtomicU64,
+    throttled: AtomicU64,
+    waited_ms: AtomicU64,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Counters of a rate limiter since it was created.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimitMetrics {
+    /// Total requests that passed the limiter.
+    pub requests: u64,
+    /// Requests that had to wait for a token.
+    pub throttled: u64,
+    /// Total time spent waiting.
+    pub waited: Duration,
+}
+
+impl RateLimiter {
+    /// Create a limiter allowing `requests_per_second` on average.
+    ///
+    /// The burst capacity is one second worth of requests (at least one).
+    /// A rate of zero or less disables limiting.
+    pub fn new(name: &'static str, requests_per_second: f64) -> Self {
+        let rate = (requests_per_second > 0.0).then_some(requests_per_second);
+        let burst = requests_per_second.max(1.0);
+
+        Self {
+            name,
+            rate,
+            burst,
+            bucket: Mutex::new(Bucket {
+                tokens: burst,
+                updated: Instant::now(),
+            }),
+            requests: AtomicU64::new(0),
+            throttled: AtomicU64::new(0),
+            waited_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Create a limiter that never waits (metrics are still collected).
+    pub fn unlimited(name: &'static str) -> Self {
+        Self::new(name, 0.0)
+    }
+
+    /// Wait until a request may be sent.
+    pub async fn acquire(&self) {
+        let wait = self.reserve(Instant::now());
+
+        if !wait.is_zero() {
+            tracing::debug!("{} rate limit: waiting {:?}", self.name, wait);
+            sleep(wait).await;
+        }
+    }
+
+    /// Stop handing out tokens for the given duration (e.g. after HTTP 429).
+    pub fn penalize(&self, duration: Duration) {
+        let Some(rate) = self.rate else {
+            return;
+        };
+
+        let mut bucket = self.bucket.lock().unwrap();
+        self.refill(&mut bucket, Instant::now(), rate);
+        bucket.tokens = bucket.tokens.min(0.0) - duration.as_secs_f64() * rate;
+    }
+
+    /// Get the counters of this limiter.
+    pub fn metrics(&self) -> RateLimitMetrics {
+        RateLimitMetrics {
+            requests: self.requests.load(Ordering::Relaxed),
+            throttled: self.throttled.load(Ordering::Relaxed),
+            waited: Duration::from_millis(self.waited_ms.load(Ordering::Relaxed)),
+        }
+    }
+
+    /// Take a token at `now` and return how long the caller has to wait for it.
+    fn reserve(&self, now: Instant) -> Duration {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+
+        let Some(rate) = self.rate else {
+            return Duration::ZERO;
+        };
+
+        let mut bucket = self.bucket.lock().unwrap();
+        self.refill(&mut bucket, now, rate);
+        bucket.tokens -= 1.0;
+
+        if bucket.tokens >= 0.0 {
+            return Duration::ZERO;
+        }
+
+        let wait = Duration::from_secs_f64(-bucket.tokens / rate);
+        self.throttled.fetch_add(1, Ordering::Relaxed);
+        self.waited_ms
+            .fetch_add(wait.as_millis() as u64, Ordering::Relaxed);
+        wait
+    }
+
+    /// Add the tokens earned since the last update.
+    fn refill(&self, bucket: &mut Bucket, now: Instant, rate: f64) {
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(self.burst);
+        bucket.updated = bucket.updated.max(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_then_queue() {
+        let limiter = RateLimiter::new("test", 2.0);
+        let now = Instant::now();
+
+        // Burst of two requests passes immediately
+        assert_eq!(limiter.reserve(now), Duration::ZERO);
+        assert_eq!(limiter.reserve(now), Duration::ZERO);
+
+        // Further requests queue behind each other
+        assert_eq!(limiter.reserve(now), Duration::from_millis(500));
+        assert_eq!(limiter.reserve(now), Duration::from_millis(1000));
+
+        let metrics = limiter.metrics();
+        assert_eq!(metrics.requests, 4);
+        assert_eq!(metrics.throttled, 2);
+        assert_eq!(metrics.waited, Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn test_refill_is_capped_at_burst() {
+        let limiter = RateLimiter::new("test", 1.0);
+        let now = Instant::now();
+
+        assert_eq!(limiter.reserve(now), Duration::ZERO);
+
+        let later = now + Duration::from_secs(10);
+        assert_eq!(limiter.reserve(later), Duration::ZERO);
+        assert_eq!(limiter.reserve(later), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_penalize_delays_next_request() {
+        let limiter = RateLimiter::new("test", 1.0);
+        limiter.penalize(Duration::from_secs(5));
+
+        let wait = limiter.reserve(Instant::now());
+        assert!(wait > Duration::from_millis(5900) && wait <= Duration::from_secs(6));
+    }
+
+    #[test]
+    fn test_unlimited_never_waits() {
+        let limiter = RateLimiter::unlimited("test");
+        let now = Instant::now();
+
+        for _ in 0..100 {
+            assert_eq!(limiter.reserve(now), Duration::ZERO);
+        }
+        limiter.penalize(Duration::from_secs(60));
+        assert_eq!(limiter.reserve(now), Duration::ZERO);
+        assert_eq!(limiter.metrics().requests, 101);
+    }
+}
@@ -0,0 +1,68 @@
+//! Local HTTP server standing in for the API in tests.
+
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Request heads (lowercased) received by a stub server.
+pub type Requests = Arc<Mutex<Vec<String>>>;
+
+/// Start an HTTP server answering each request with the status and body
+/// returned by `handler` for its path (including the query), and recording
+/// the request heads.
+pub async fn spawn_server<F>(handler: F) -> (String, Requests)
+where
+    F: Fn(&str) -> (u16, String) + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&requests);
+    let handler = Arc::new(handler);
+
+    tokio::spawn(async move {
+        while let Ok((mut tcp, _)) = listener.accept().await {
+            let recorded = Arc::clone(&recorded);
+            let handler = Arc::clone(&handler);
+            tokio::spawn(async move {
+                let mut head = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !head.ends_with(b"\r\n\r\n") {
+                    match tcp.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => head.extend_from_slice(&buf[..n]),
+                    }
+                }
+
+                let head = String::from_utf8_lossy(&head).into_owned();
+                let path = head.split_whitespace().nth(1).unwrap_or_default();
+                let (status, body) = handler(path);
+                recorded.lock().unwrap().push(head.to_lowercase());
+
+                let response = format!(
+                    "HTTP/1.1 {} Stub\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = tcp.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+
+    (url, requests)
+}
+
+/// Start an HTTP server answering paths starting with a route with its
+/// status and body, and anything else with 404.
+pub async fn spawn_routes(routes: Vec<(&'static str, u16, &'static str)>) -> (String, Requests) {
+    spawn_server(move |path| {
+        routes
+            .iter()
+            .find(|(route, _, _)| path.starts_with(route))
+            .map(|(_, status, body)| (*status, body.to_string()))
+            .unwrap_or((404, "{}".to_string()))
+    })
+    .await
+}
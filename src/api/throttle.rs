@@ -0,0 +1,234 @@
+//! Bandwidth throttling for media downloads.
+//!
+//! A single [`BandwidthLimiter`] is shared by every download (direct files and
+//! HLS segments, across all creators), so the configured cap applies to the
+//! total transfer rate. The cap can change over the day via a schedule.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{Local, NaiveTime, Timelike};
+use futures::{Stream, StreamExt};
+use tokio::time::sleep;
+
+/// Bandwidth limits over the day, in bytes per second (`None` = unlimited).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BandwidthSchedule {
+    default_limit: Option<u64>,
+    windows: Vec<(NaiveTime, NaiveTime, Option<u64>)>,
+}
+
+impl BandwidthSchedule {
+    /// Create a schedule with a default limit and `(start, end, limit)`
+    /// windows; a window may wrap past midnight.
+    pub fn new(
+        default_limit: Option<u64>,
+        windows: Vec<(NaiveTime, NaiveTime, Option<u64>)>,
+    ) -> Self {
+        Self {
+            default_limit,
+            windows,
+        }
+    }
+
+    /// Get the limit at a time of day. The first matching window wins.
+    pub fn limit_at(&self, time: NaiveTime) -> Option<u64> {
+        for &(start, end, limit) in &self.windows {
+            let inside = if start <= end {
+                time >= start && time < end
+            } else {
+                time >= start || time < end
+            };
+            if inside {
+                return limit;
+            }
+        }
+        self.default_limit
+    }
+
+    /// Check whether downloads are never limited.
+    pub fn is_unlimited(&self) -> bool {
+        self.default_limit.is_none() && self.windows.iter().all(|w| w.2.is_none())
+    }
+}
+
+/// Shared byte-rate limiter.
+#[derive(Debug, Default)]
+pub struct BandwidthLimiter {
+    schedule: BandwidthSchedule,
+    bucket: Mutex<Option<(f64, Instant)>>,
+}
+
+impl BandwidthLimiter {
+    /// Create a limiter following a schedule.
+    pub fn new(schedule: BandwidthSchedule) -> Self {
+        Self {
+            schedule,
+            bucket: Mutex::new(None),
+        }
+    }
+
+    /// Create a limiter that never waits.
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Account for `bytes` received, waiting if the current limit is exceeded.
+    pub async fn consume(&self, bytes: usize) {
+        if self.schedule.is_unlimited() {
+            return;
+        }
+
+        let now = Local::now().time();
+        let wait = self.reserve(bytes, self.schedule.limit_at(now), Instant::now());
+        if !wait.is_zero() {
+            sleep(wait).await;
+        }
+    }
+
+    /// Take `bytes` tokens at `now` under `limit` and return the required wait.
+    ///
+    /// The bucket holds at most one second worth of bytes and may go negative,
+    /// so concurrent downloads queue behind each other.
+    fn reserve(&self, bytes: usize, limit: Option<u64>, now: Instant) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap();
+
+        let Some(rate) = limit.map(|l| l as f64) else {
+            *bucket = None;
+            return Duration::ZERO;
+        };
+
+        let (tokens, updated) = bucket.get_or_insert((rate, now));
+        let elapsed = now.saturating_duration_since(*updated).as_secs_f64();
+        *tokens = (*tokens + elapsed * rate).min(rate) - bytes as f64;
+        *updated = (*updated).max(now);
+
+        if *tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-*tokens / rate)
+        }
+    }
+}
+
+/// Wrap a byte stream so that it is read no faster than the limiter allows.
+pub fn throttle<'a, S, B, E>(
+    stream: S,
+    limiter: &'a BandwidthLimiter,
+) -> impl Stream<Item = std::result::Result<B, E>> + 'a
+where
+    S: Stream<Item = std::result::Result<B, E>> + 'a,
+    B: AsRef<[u8]> + 'a,
+    E: 'a,
+{
+    stream.then(move |chunk| async move {
+        if let Ok(bytes) = &chunk {
+            limiter.consume(bytes.as_ref().len()).await;
+        }
+        chunk
+    })
+}
+
+/// Parse a rate like "8MB/s", "500 KiB", "1.5M" or "unlimited" into bytes per second.
+///
+/// Decimal units (KB, MB, GB) use powers of 1000, binary units (KiB, MiB, GiB)
+/// powers of 1024. Zero means unlimited.
+pub fn parse_rate(input: &str) -> std::result::Result<Option<u64>, String> {
+    let value = input.trim().to_ascii_lowercase();
+    let value = value.strip_suffix("/s").unwrap_or(&value).trim();
+
+    if value.is_empty() || value == "unlimited" || value == "none" {
+        return Ok(None);
+    }
+
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("Invalid rate: {}", input))?;
+
+    let multiplier: f64 = match unit.trim() {
+        "" | "b" => 1.0,
+        "k" | "kb" => 1e3,
+        "m" | "mb" => 1e6,
+        "g" | "gb" => 1e9,
+        "kib" => 1024.0,
+        "mib" => 1024.0 * 1024.0,
+        "gib" => 1024.0 * 1024.0 * 1024.0,
+        other => return Err(format!("Unknown rate unit '{}' in: {}", other, input)),
+    };
+
+    let bytes = (number * multiplier).round() as u64;
+    Ok((bytes > 0).then_some(bytes))
+}
+
+/// Parse a local time of day ("HH:MM").
+pub fn parse_time(input: &str) -> std::result::Result<NaiveTime, String> {
+    let time = NaiveTime::parse_from_str(input.trim(), "%H:%M")
+        .map_err(|_| format!("Invalid time (expected HH:MM): {}", input))?;
+    Ok(time.with_second(0).unwrap_or(time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_rate() {
+        assert_eq!(parse_rate("8MB/s").unwrap(), Some(8_000_000));
+        assert_eq!(parse_rate("8 MB/s").unwrap(), Some(8_000_000));
+        assert_eq!(parse_rate("500KiB").unwrap(), Some(512_000));
+        assert_eq!(parse_rate("1.5m").unwrap(), Some(1_500_000));
+        assert_eq!(parse_rate("1024").unwrap(), Some(1024));
+        assert_eq!(parse_rate("unlimited").unwrap(), None);
+        assert_eq!(parse_rate("0").unwrap(), None);
+        assert!(parse_rate("fast").is_err());
+        assert!(parse_rate("10 parsecs").is_err());
+    }
+
+    #[test]
+    fn test_schedule_windows() {
+        let schedule = BandwidthSchedule::new(
+            Some(8_000_000),
+            vec![
+                (time(23, 0), time(7, 0), None),
+                (time(12, 0), time(13, 0), Some(1_000_000)),
+            ],
+        );
+
+        assert_eq!(schedule.limit_at(time(2, 0)), None);
+        assert_eq!(schedule.limit_at(time(23, 30)), None);
+        assert_eq!(schedule.limit_at(time(7, 0)), Some(8_000_000));
+        assert_eq!(schedule.limit_at(time(12, 30)), Some(1_000_000));
+        assert!(!schedule.is_unlimited());
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("07:30").unwrap(), time(7, 30));
+        assert!(parse_time("25:00").is_err());
+    }
+
+    #[test]
+    fn test_reserve_shares_budget() {
+        let limiter = BandwidthLimiter::unlimited();
+        let now = Instant::now();
+        let limit = Some(1000);
+
+        // One second worth passes immediately, the rest queues
+        assert_eq!(limiter.reserve(1000, limit, now), Duration::ZERO);
+        assert_eq!(limiter.reserve(500, limit, now), Duration::from_millis(500));
+        assert_eq!(limiter.reserve(500, limit, now), Duration::from_secs(1));
+
+        // Lifting the limit clears the debt
+        assert_eq!(limiter.reserve(10_000, None, now), Duration::ZERO);
+        assert_eq!(limiter.reserve(1000, limit, now), Duration::ZERO);
+    }
+}
@@ -26,6 +26,34 @@ pub struct AccountInfo {
     pub following: Option<bool>,
     pub subscribed: Option<bool>,
     pub timeline_stats: Option<TimelineStats>,
+    #[serde(default)]
+    pub walls: Vec<Wall>,
+    pub about: Option<String>,
+    pub location: Option<String>,
+    pub follow_count: Option<u64>,
+    pub subscriber_count: Option<u64>,
+    pub avatar: Option<MediaDetails>,
+    pub banner: Option<MediaDetails>,
+}
+
+/// A timeline wall (a named subset of a creator's posts).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Wall {
+    pub id: String,
+    pub pos: Option<i32>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+impl Wall {
+    /// Get a display name for the wall, falling back to its ID.
+    pub fn display_name(&self) -> &str {
+        self.name
+            .as_deref()
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or(&self.id)
+    }
 }
 
 /// Timeline statistics.
@@ -34,6 +62,7 @@ pub struct AccountInfo {
 pub struct TimelineStats {
     pub image_count: Option<u64>,
     pub video_count: Option<u64>,
+    pub bundle_count: Option<u64>,
 }
 
 /// Timeline response containing posts and media.
@@ -128,6 +157,14 @@ pub struct Attachment {
     pub content_type: i32,
 }
 
+impl Attachment {
+    /// Attachment content type referring to a single account media item.
+    pub const ACCOUNT_MEDIA: i32 = 1;
+
+    /// Attachment content type referring to a media bundle.
+    pub const MEDIA_BUNDLE: i32 = 2;
+}
+
 /// Messages response.
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -148,6 +185,8 @@ pub struct Message {
     pub sender_id: String,
     pub created_at: i64,
     #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default)]
     pub attachments: Vec<Attachment>,
 }
 
@@ -177,7 +216,7 @@ pub struct GroupsResponse {
 }
 
 /// Post response for single post download.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PostResponse {
     #[serde(default)]
@@ -208,6 +247,37 @@ pub struct MediaOrder {
     pub bundle_id: Option<String>,
 }
 
+/// Active stories response.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoriesResponse {
+    #[serde(default)]
+    pub media_stories: Vec<MediaStory>,
+    #[serde(default)]
+    pub aggregation_data: StoriesAggregationData,
+}
+
+/// A time-limited story.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaStory {
+    pub id: String,
+    pub account_id: String,
+    pub content_type: i32,
+    pub content_id: String,
+    pub created_at: i64,
+}
+
+/// Media referenced by stories.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoriesAggregationData {
+    #[serde(default)]
+    pub account_media: Vec<AccountMedia>,
+    #[serde(default)]
+    pub account_media_bundles: Vec<MediaBundle>,
+}
+
 /// Device ID response.
 #[derive(Debug, Deserialize)]
 pub struct DeviceIdResponse {
@@ -215,6 +285,24 @@ pub struct DeviceIdResponse {
     pub device_id: String,
 }
 
+/// Device ID endpoint payload: a plain string or a `DeviceIdResponse` object.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum DeviceIdPayload {
+    Plain(String),
+    Object(DeviceIdResponse),
+}
+
+impl DeviceIdPayload {
+    /// Get the device ID.
+    pub fn into_device_id(self) -> String {
+        match self {
+            DeviceIdPayload::Plain(device_id) => device_id,
+            DeviceIdPayload::Object(response) => response.device_id,
+        }
+    }
+}
+
 /// WebSocket authentication message.
 #[derive(Debug, Serialize)]
 pub struct WsAuthMessage {
@@ -248,3 +336,20 @@ pub struct MediaInfoResponse {
     #[serde(default)]
     pub account_media: Vec<AccountMedia>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_device_id_payload() {
+        let plain: ApiResponse<DeviceIdPayload> =
+            serde_json::from_str(r#"{"success":true,"response":"123456789"}"#).unwrap();
+        assert_eq!(plain.response.into_device_id(), "123456789");
+
+        let object: ApiResponse<DeviceIdPayload> =
+            serde_json::from_str(r#"{"success":true,"response":{"deviceId":"987654321"}}"#)
+                .unwrap();
+        assert_eq!(object.response.into_device_id(), "987654321");
+    }
+}
@@ -1,13 +1,28 @@
 //! WebSocket session management for Fansly API.
+//!
+//! API requests carry the ID of an authenticated WebSocket session. The
+//! session only stays valid while its connection is open, so [`SessionKeeper`]
+//! holds the connection in a background task, pings it, and reconnects (with a
+//! new session ID) whenever it drops. Real-time events received on the
+//! connection are decoded into [`WsEvent`]s and broadcast to subscribers.
 
-use futures::{SinkExt, StreamExt};
+use std::sync::Arc;
 use std::time::Duration;
-use tokio::time::timeout;
+
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use tokio::net::TcpStream;
+use tokio::sync::{broadcast, mpsc, oneshot, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::{interval, sleep, timeout, Instant, MissedTickBehavior};
 use tokio_tungstenite::{
-    connect_async,
+    client_async_tls,
     tungstenite::{handshake::client::Request, Message},
+    MaybeTlsStream, WebSocketStream,
 };
+use url::Url;
 
+use crate::api::proxy::{ProxyConfig, Tunnel};
 use crate::api::types::WsSessionData;
 use crate::error::{Error, Result};
 
@@ -17,14 +32,423 @@ const WS_URL: &str = "wss://wsv3.fansly.com";
 /// WebSocket connection timeout.
 const WS_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// Interval between keepalive pings.
+const PING_INTERVAL: Duration = Duration::from_secs(20);
+
+/// Initial delay before reconnecting, doubled on every failed attempt.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Maximum delay between reconnection attempts.
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+/// Number of undelivered events kept per subscriber.
+const EVENT_CAPACITY: usize = 256;
+
+/// Frame type of service events.
+const SERVICE_EVENT_FRAME: i64 = 10000;
+
+type WsStream = WebSocketStream<MaybeTlsStream<Box<dyn Tunnel>>>;
+
+/// Where and how to open WebSocket sessions.
+#[derive(Clone)]
+struct Endpoint {
+    url: String,
+    token: String,
+    user_agent: String,
+    proxy: Option<ProxyConfig>,
+}
+
 /// Establish a WebSocket connection and obtain a session ID.
-pub async fn get_session_id(token: &str, user_agent: &str) -> Result<String> {
+///
+/// The connection is closed afterwards, so the session only lives briefly. Use
+/// [`SessionKeeper`] for sessions that must outlast a download run.
+pub async fn get_session_id(
+    token: &str,
+    user_agent: &str,
+    proxy: Option<&ProxyConfig>,
+) -> Result<String> {
+    let endpoint = Endpoint {
+        url: WS_URL.to_string(),
+        token: token.to_string(),
+        user_agent: user_agent.to_string(),
+        proxy: proxy.cloned(),
+    };
+
+    let (mut stream, session_id) = open_session(&endpoint).await?;
+    let _ = stream.close(None).await;
+    Ok(session_id)
+}
+
+/// A real-time event announced over the WebSocket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WsEvent {
+    /// A creator published a post.
+    NewPost { account_id: String, post_id: String },
+    /// A message was received in a group.
+    NewMessage {
+        account_id: String,
+        group_id: String,
+        message_id: String,
+    },
+    /// A creator published a story.
+    NewStory {
+        account_id: String,
+        story_id: String,
+    },
+    /// Any other service event.
+    Other { service_id: i64, event_type: i64 },
+}
+
+impl WsEvent {
+    /// Get the account that caused the event, if known.
+    pub fn account_id(&self) -> Option<&str> {
+        match self {
+            WsEvent::NewPost { account_id, .. }
+            | WsEvent::NewMessage { account_id, .. }
+            | WsEvent::NewStory { account_id, .. } => Some(account_id),
+            WsEvent::Other { .. } => None,
+        }
+    }
+}
+
+/// Outer WebSocket frame: `{"t": <type>, "d": "<JSON string>"}`.
+#[derive(Deserialize)]
+struct Frame {
+    t: i64,
+    #[serde(default)]
+    d: Option<String>,
+}
+
+/// Service event frame payload; the event itself is another JSON string.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ServiceEvent {
+    service_id: i64,
+    event: String,
+}
+
+/// Service event body. Events are told apart by the object they carry.
+#[derive(Deserialize)]
+struct EventBody {
+    #[serde(rename = "type", default)]
+    event_type: i64,
+    #[serde(default)]
+    post: Option<EventPost>,
+    #[serde(default)]
+    message: Option<EventMessage>,
+    #[serde(default, alias = "mediaStory")]
+    story: Option<EventStory>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EventPost {
+    id: String,
+    account_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EventMessage {
+    id: String,
+    group_id: String,
+    sender_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EventStory {
+    id: String,
+    account_id: String,
+}
+
+/// Decode a WebSocket text frame into an event.
+///
+/// Returns `None` for frames that aren't service events (e.g. session replies)
+/// or that can't be parsed.
+pub fn decode_event(text: &str) -> Option<WsEvent> {
+    let frame: Frame = serde_json::from_str(text).ok()?;
+    if frame.t != SERVICE_EVENT_FRAME {
+        return None;
+    }
+
+    let service: ServiceEvent = serde_json::from_str(frame.d.as_deref()?).ok()?;
+    let body: EventBody = serde_json::from_str(&service.event).ok()?;
+
+    Some(if let Some(post) = body.post {
+        WsEvent::NewPost {
+            account_id: post.account_id,
+            post_id: post.id,
+        }
+    } else if let Some(message) = body.message {
+        WsEvent::NewMessage {
+            account_id: message.sender_id,
+            group_id: message.group_id,
+            message_id: message.id,
+        }
+    } else if let Some(story) = body.story {
+        WsEvent::NewStory {
+            account_id: story.account_id,
+            story_id: story.id,
+        }
+    } else {
+        WsEvent::Other {
+            service_id: service.service_id,
+            event_type: body.event_type,
+        }
+    })
+}
+
+/// A long-lived WebSocket session, kept open and renewed in the background.
+pub struct SessionKeeper {
+    session_id: Arc<RwLock<String>>,
+    events: broadcast::Sender<WsEvent>,
+    refresh_tx: mpsc::Sender<RefreshRequest>,
+    task: JoinHandle<()>,
+}
+
+/// Request to replace a session that the API rejected.
+struct RefreshRequest {
+    stale: String,
+    reply: oneshot::Sender<Result<String>>,
+}
+
+/// Why a connection stopped being served.
+enum Interruption {
+    Dropped,
+    Refresh(RefreshRequest),
+    Shutdown,
+}
+
+impl SessionKeeper {
+    /// Open a session and keep it alive until the keeper is dropped.
+    ///
+    /// With a proxy, the connection is tunneled through it before the TLS handshake.
+    pub async fn start(token: &str, user_agent: &str, proxy: Option<&ProxyConfig>) -> Result<Self> {
+        Self::start_at(WS_URL, token, user_agent, proxy).await
+    }
+
+    /// Open a session against a specific WebSocket URL.
+    pub(crate) async fn start_at(
+        url: &str,
+        token: &str,
+        user_agent: &str,
+        proxy: Option<&ProxyConfig>,
+    ) -> Result<Self> {
+        let endpoint = Endpoint {
+            url: url.to_string(),
+            token: token.to_string(),
+            user_agent: user_agent.to_string(),
+            proxy: proxy.cloned(),
+        };
+
+        // The first connection is made here so that errors reach the caller
+        let (stream, session_id) = open_session(&endpoint).await?;
+        let session_id = Arc::new(RwLock::new(session_id));
+        let (refresh_tx, refresh_rx) = mpsc::channel(8);
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+        let task = tokio::spawn(keep_alive(
+            endpoint,
+            stream,
+            Arc::clone(&session_id),
+            refresh_rx,
+            events.clone(),
+        ));
+
+        Ok(Self {
+            session_id,
+            events,
+            refresh_tx,
+            task,
+        })
+    }
+
+    /// Get the current session ID.
+    pub async fn session_id(&self) -> String {
+        self.session_id.read().await.clone()
+    }
+
+    /// Subscribe to real-time events.
+    ///
+    /// Events arriving while the connection is being re-established are lost.
+    pub fn subscribe(&self) -> broadcast::Receiver<WsEvent> {
+        self.events.subscribe()
+    }
+
+    /// Replace the session after the API rejected `stale`.
+    ///
+    /// If the session was already replaced since `stale` was read (e.g. by a
+    /// parallel request), the current ID is returned without reconnecting.
+    pub async fn refresh(&self, stale: &str) -> Result<String> {
+        let (reply, response) = oneshot::channel();
+        let request = RefreshRequest {
+            stale: stale.to_string(),
+            reply,
+        };
+
+        self.refresh_tx
+            .send(request)
+            .await
+            .map_err(|_| Error::Api("WebSocket session task stopped".into()))?;
+        response
+            .await
+            .map_err(|_| Error::Api("WebSocket session task stopped".into()))?
+    }
+}
+
+impl Drop for SessionKeeper {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Serve the connection, reconnecting whenever it drops or a refresh is requested.
+async fn keep_alive(
+    endpoint: Endpoint,
+    mut stream: WsStream,
+    session_id: Arc<RwLock<String>>,
+    mut requests: mpsc::Receiver<RefreshRequest>,
+    events: broadcast::Sender<WsEvent>,
+) {
+    loop {
+        let mut pending = match serve(&mut stream, &session_id, &mut requests, &events).await {
+            Interruption::Shutdown => return,
+            Interruption::Dropped => {
+                tracing::warn!("WebSocket session dropped, reconnecting");
+                None
+            }
+            Interruption::Refresh(request) => {
+                tracing::info!("Refreshing WebSocket session");
+                let _ = stream.close(None).await;
+                Some(request)
+            }
+        };
+
+        let mut delay = RECONNECT_DELAY;
+        stream = loop {
+            match open_session(&endpoint).await {
+                Ok((new_stream, new_id)) => {
+                    tracing::debug!("New WebSocket session {}", new_id);
+                    *session_id.write().await = new_id.clone();
+                    if let Some(request) = pending.take() {
+                        let _ = request.reply.send(Ok(new_id));
+                    }
+                    break new_stream;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "WebSocket reconnect failed, retrying in {}s: {}",
+                        delay.as_secs(),
+                        e
+                    );
+                    if let Some(request) = pending.take() {
+                        let _ = request.reply.send(Err(e));
+                    }
+                }
+            }
+
+            // Refresh requests can't be served until a connection is back
+            let wait = sleep(delay);
+            tokio::pin!(wait);
+            loop {
+                tokio::select! {
+                    _ = &mut wait => break,
+                    request = requests.recv() => match request {
+                        Some(request) => {
+                            let _ = request
+                                .reply
+                                .send(Err(Error::Api("WebSocket session is reconnecting".into())));
+                        }
+                        None => return,
+                    },
+                }
+            }
+            delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+        };
+    }
+}
+
+/// Keep a connection alive with pings until it drops or must be replaced.
+async fn serve(
+    stream: &mut WsStream,
+    session_id: &RwLock<String>,
+    requests: &mut mpsc::Receiver<RefreshRequest>,
+    events: &broadcast::Sender<WsEvent>,
+) -> Interruption {
+    let mut ping = interval(PING_INTERVAL);
+    ping.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    ping.tick().await;
+    let mut last_seen = Instant::now();
+
+    loop {
+        tokio::select! {
+            _ = ping.tick() => {
+                // Nothing (not even a pong) since the previous ping
+                if last_seen.elapsed() > PING_INTERVAL * 2 {
+                    tracing::debug!("WebSocket keepalive timed out");
+                    return Interruption::Dropped;
+                }
+                if let Err(e) = stream.send(Message::Ping(Vec::new())).await {
+                    tracing::debug!("WebSocket ping failed: {}", e);
+                    return Interruption::Dropped;
+                }
+            }
+            message = stream.next() => match message {
+                Some(Ok(Message::Close(_))) | None => return Interruption::Dropped,
+                Some(Err(e)) => {
+                    tracing::debug!("WebSocket error: {}", e);
+                    return Interruption::Dropped;
+                }
+                Some(Ok(message)) => {
+                    last_seen = Instant::now();
+                    if let Message::Text(text) = message {
+                        if let Some(event) = decode_event(&text) {
+                            tracing::debug!("WebSocket event: {:?}", event);
+                            // Nobody may be listening
+                            let _ = events.send(event);
+                        }
+                    }
+                }
+            },
+            request = requests.recv() => match request {
+                Some(request) => {
+                    let current = session_id.read().await.clone();
+                    if current != request.stale {
+                        let _ = request.reply.send(Ok(current));
+                    } else {
+                        return Interruption::Refresh(request);
+                    }
+                }
+                None => return Interruption::Shutdown,
+            },
+        }
+    }
+}
+
+/// Connect to the WebSocket endpoint and authenticate, returning the open
+/// connection and its session ID.
+async fn open_session(endpoint: &Endpoint) -> Result<(WsStream, String)> {
+    let mut stream = timeout(WS_TIMEOUT, connect(endpoint))
+        .await
+        .map_err(|_| Error::Api("WebSocket connection timeout".into()))??;
+    let session_id = authenticate(&mut stream, &endpoint.token).await?;
+    Ok((stream, session_id))
+}
+
+/// Open the WebSocket connection, directly or through the proxy.
+async fn connect(endpoint: &Endpoint) -> Result<WsStream> {
+    let url = Url::parse(&endpoint.url)?;
+    let host = url
+        .host_str()
+        .ok_or_else(|| Error::Api(format!("Invalid WebSocket URL: {}", endpoint.url)))?;
+    let port = url.port_or_known_default().unwrap_or(443);
+
     // Build request with required headers
     let request = Request::builder()
-        .uri(WS_URL)
-        .header("User-Agent", user_agent)
+        .uri(endpoint.url.as_str())
+        .header("User-Agent", &endpoint.user_agent)
         .header("Origin", "https://fansly.com")
-        .header("Host", "wsv3.fansly.com")
+        .header("Host", host)
         .header("Connection", "Upgrade")
         .header("Upgrade", "websocket")
         .header("Sec-WebSocket-Version", "13")
@@ -35,10 +459,17 @@ pub async fn get_session_id(token: &str, user_agent: &str) -> Result<String> {
         .body(())
         .map_err(|e| Error::Api(format!("Failed to build WebSocket request: {}", e)))?;
 
-    // Connect to WebSocket with headers
-    let (ws_stream, _) = connect_async(request).await?;
-    let (mut write, mut read) = ws_stream.split();
+    let tcp: Box<dyn Tunnel> = match &endpoint.proxy {
+        Some(proxy) => proxy.connect(host, port).await?,
+        None => Box::new(TcpStream::connect((host, port)).await?),
+    };
+
+    let (stream, _) = client_async_tls(request, tcp).await?;
+    Ok(stream)
+}
 
+/// Send the auth message and read the session ID from the response.
+async fn authenticate(stream: &mut WsStream, token: &str) -> Result<String> {
     // Build auth message - format must match exactly what Fansly expects
     // The 'd' field is a JSON string containing the token object
     // Result: {"t":1,"d":"{\"token\":\"TOKEN\"}"}
@@ -48,10 +479,10 @@ pub async fn get_session_id(token: &str, user_agent: &str) -> Result<String> {
     tracing::debug!("Sending WebSocket auth message: {}", auth_json);
 
     // Send authentication message
-    write.send(Message::Text(auth_json)).await?;
+    stream.send(Message::Text(auth_json)).await?;
 
     // Read response with timeout
-    let response = timeout(WS_TIMEOUT, read.next())
+    let response = timeout(WS_TIMEOUT, stream.next())
         .await
         .map_err(|_| Error::Api("WebSocket response timeout".into()))?
         .ok_or_else(|| Error::Api("WebSocket closed without response".into()))??;
@@ -84,5 +515,167 @@ pub async fn get_session_id(token: &str, user_agent: &str) -> Result<String> {
 
 #[cfg(test)]
 mod tests {
-    // WebSocket tests would require mocking or integration test setup
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::accept_async;
+
+    /// Build a service event frame the way Fansly nests it (JSON in JSON in JSON).
+    fn event_frame(service_id: i64, event: serde_json::Value) -> String {
+        let service = serde_json::json!({ "serviceId": service_id, "event": event.to_string() });
+        serde_json::json!({ "t": SERVICE_EVENT_FRAME, "d": service.to_string() }).to_string()
+    }
+
+    /// Start a WebSocket server that hands out sessions `s1`, `s2`, ..., replays
+    /// `frames` on the first connection, and closes it afterwards when
+    /// `drop_first` is set.
+    async fn spawn_server(drop_first: bool, frames: Vec<String>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let sessions = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&sessions);
+
+        tokio::spawn(async move {
+            while let Ok((tcp, _)) = listener.accept().await {
+                let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                let frames = if n == 1 { frames.clone() } else { Vec::new() };
+                tokio::spawn(async move {
+                    let mut ws = accept_async(tcp).await.unwrap();
+                    let Some(Ok(Message::Text(auth))) = ws.next().await else {
+                        return;
+                    };
+                    assert!(auth.contains("secret"));
+
+                    let inner = format!(r#"{{"session":{{"id":"s{}"}}}}"#, n);
+                    let reply = serde_json::json!({ "t": 1, "d": inner }).to_string();
+                    ws.send(Message::Text(reply)).await.unwrap();
+
+                    if !frames.is_empty() {
+                        // Give the client time to subscribe
+                        sleep(Duration::from_millis(100)).await;
+                        for frame in frames {
+                            ws.send(Message::Text(frame)).await.unwrap();
+                        }
+                    }
+
+                    if drop_first && n == 1 {
+                        let _ = ws.close(None).await;
+                        return;
+                    }
+                    while let Some(Ok(_)) = ws.next().await {}
+                });
+            }
+        });
+
+        (url, sessions)
+    }
+
+    #[tokio::test]
+    async fn test_refresh_replaces_stale_session() {
+        let (url, sessions) = spawn_server(false, Vec::new()).await;
+        let keeper = SessionKeeper::start_at(&url, "secret", "test", None)
+            .await
+            .unwrap();
+        assert_eq!(keeper.session_id().await, "s1");
+
+        assert_eq!(keeper.refresh("s1").await.unwrap(), "s2");
+        assert_eq!(keeper.session_id().await, "s2");
+
+        // A refresh for an already replaced session doesn't reconnect
+        assert_eq!(keeper.refresh("s1").await.unwrap(), "s2");
+        assert_eq!(sessions.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_reconnects_after_drop() {
+        let (url, _) = spawn_server(true, Vec::new()).await;
+        let keeper = SessionKeeper::start_at(&url, "secret", "test", None)
+            .await
+            .unwrap();
+
+        let mut session_id = keeper.session_id().await;
+        for _ in 0..50 {
+            if session_id != "s1" {
+                break;
+            }
+            sleep(Duration::from_millis(20)).await;
+            session_id = keeper.session_id().await;
+        }
+        assert_eq!(session_id, "s2");
+    }
+
+    #[test]
+    fn test_decode_events() {
+        let post = event_frame(
+            1,
+            serde_json::json!({ "type": 1, "post": { "id": "p1", "accountId": "a1" } }),
+        );
+        assert_eq!(
+            decode_event(&post),
+            Some(WsEvent::NewPost {
+                account_id: "a1".into(),
+                post_id: "p1".into()
+            })
+        );
+
+        let message = event_frame(
+            46,
+            serde_json::json!({
+                "type": 1,
+                "message": { "id": "m1", "groupId": "g1", "senderId": "a2", "content": "hi" }
+            }),
+        );
+        assert_eq!(
+            decode_event(&message),
+            Some(WsEvent::NewMessage {
+                account_id: "a2".into(),
+                group_id: "g1".into(),
+                message_id: "m1".into()
+            })
+        );
+
+        let story = event_frame(
+            32,
+            serde_json::json!({ "type": 1, "mediaStory": { "id": "st1", "accountId": "a3" } }),
+        );
+        assert_eq!(decode_event(&story).unwrap().account_id(), Some("a3"));
+
+        let other = event_frame(15, serde_json::json!({ "type": 7 }));
+        assert_eq!(
+            decode_event(&other),
+            Some(WsEvent::Other {
+                service_id: 15,
+                event_type: 7
+            })
+        );
+
+        // Session replies and garbage aren't events
+        assert_eq!(decode_event(r#"{"t":1,"d":"{}"}"#), None);
+        assert_eq!(decode_event("not json"), None);
+    }
+
+    #[tokio::test]
+    async fn test_events_are_broadcast() {
+        let frames = vec![
+            event_frame(
+                1,
+                serde_json::json!({ "type": 1, "post": { "id": "p1", "accountId": "a1" } }),
+            ),
+            r#"{"t":2,"d":"ping"}"#.to_string(),
+            event_frame(
+                32,
+                serde_json::json!({ "type": 1, "story": { "id": "st1", "accountId": "a1" } }),
+            ),
+        ];
+        let (url, _) = spawn_server(false, frames).await;
+        let keeper = SessionKeeper::start_at(&url, "secret", "test", None)
+            .await
+            .unwrap();
+        let mut events = keeper.subscribe();
+
+        let first = timeout(WS_TIMEOUT, events.recv()).await.unwrap().unwrap();
+        assert!(matches!(first, WsEvent::NewPost { ref post_id, .. } if post_id == "p1"));
+        let second = timeout(WS_TIMEOUT, events.recv()).await.unwrap().unwrap();
+        assert!(matches!(second, WsEvent::NewStory { ref story_id, .. } if story_id == "st1"));
+    }
 }
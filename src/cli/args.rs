@@ -1,9 +1,10 @@
 //! Command-line argument definitions using clap.
 
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
 use crate::config::{Config, DownloadMode};
+use crate::error::{Error, Result};
 
 /// Fansly content downloader CLI.
 #[derive(Parser, Debug)]
@@ -12,45 +13,109 @@ use crate::config::{Config, DownloadMode};
     version,
     about = "Download media content from Fansly creators",
     long_about = "A CLI tool to download photos, videos, and audio from Fansly creators.\n\n\
-                  Supports downloading from timeline, messages, single posts, and purchased collections."
+                  Supports downloading from timeline, messages, single posts, purchased collections, and stories."
 )]
 pub struct Args {
+    /// Command to run (downloads when omitted).
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Creator username(s) to download from.
     /// Can specify multiple users separated by spaces.
-    #[arg(short, long, value_delimiter = ' ', num_args = 1..)]
+    #[arg(short, long, global = true, value_delimiter = ' ', num_args = 1..)]
     pub user: Option<Vec<String>>,
 
     /// Base directory for downloads.
-    #[arg(short = 'd', long = "directory")]
+    #[arg(short = 'd', long = "directory", global = true)]
     pub download_directory: Option<PathBuf>,
 
     /// Fansly authorization token.
-    #[arg(short, long, env = "FANSLY_TOKEN")]
+    #[arg(short, long, global = true, env = "FANSLY_TOKEN")]
     pub token: Option<String>,
 
     /// Browser user agent string.
-    #[arg(short = 'a', long = "user-agent", env = "FANSLY_USER_AGENT")]
+    #[arg(
+        short = 'a',
+        long = "user-agent",
+        global = true,
+        env = "FANSLY_USER_AGENT"
+    )]
     pub user_agent: Option<String>,
 
     /// Fansly check key for request signing.
-    #[arg(short = 'k', long = "check-key", env = "FANSLY_CHECK_KEY")]
+    #[arg(
+        short = 'k',
+        long = "check-key",
+        global = true,
+        env = "FANSLY_CHECK_KEY"
+    )]
     pub check_key: Option<String>,
 
     /// Device ID (from fansly-d cookie or Fansly-Client-Id header).
-    #[arg(long = "device-id", env = "FANSLY_DEVICE_ID")]
+    #[arg(long = "device-id", global = true, env = "FANSLY_DEVICE_ID")]
     pub device_id: Option<String>,
 
+    /// Path to configuration file
+    /// (default: ./config.toml if present, else the user config directory).
+    #[arg(short, long, global = true, env = "FANSLY_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Account profile to use ([profiles.<name>] in the config file).
+    #[arg(long, global = true, env = "FANSLY_PROFILE")]
+    pub profile: Option<String>,
+
+    /// Hide download progress information.
+    #[arg(long, short, global = true)]
+    pub quiet: bool,
+
+    /// Maximum API requests per second (0 = unlimited).
+    #[arg(long, global = true, value_name = "RPS")]
+    pub api_rps: Option<f64>,
+
+    /// Maximum CDN download requests per second (0 = unlimited).
+    #[arg(long, global = true, value_name = "RPS")]
+    pub cdn_rps: Option<f64>,
+
+    /// Proxy URL for all traffic (http://, https://, socks5://, socks5h://; "none" for direct).
+    #[arg(long, global = true, value_name = "URL")]
+    pub proxy: Option<String>,
+
+    /// Enable debug logging.
+    #[arg(long, global = true)]
+    pub debug: bool,
+
+    /// Same as the `list walls` command (kept for older scripts).
+    #[arg(long)]
+    pub list_walls: bool,
+
+    /// Download options, also accepted without the `download` command.
+    #[command(flatten)]
+    pub download: DownloadArgs,
+}
+
+/// Options of the `download` command.
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct DownloadArgs {
     /// Download mode.
     #[arg(long, value_enum)]
     pub mode: Option<DownloadModeArg>,
 
-    /// Post ID or URL for single post download (requires --mode single).
+    /// Post ID(s) or URL(s) for single post download (implies --mode single).
+    /// The creator of each post is detected automatically.
+    #[arg(long, value_delimiter = ' ', num_args = 1..)]
+    pub post: Option<Vec<String>>,
+
+    /// File with post IDs or URLs, one per line (implies --mode single).
     #[arg(long)]
-    pub post: Option<String>,
+    pub post_file: Option<PathBuf>,
 
-    /// Path to configuration file.
-    #[arg(short, long, default_value = "config.toml")]
-    pub config: PathBuf,
+    /// Timeline wall to download: wall ID, wall name, or "all" for every wall.
+    #[arg(long)]
+    pub wall: Option<String>,
+
+    /// Only download timeline posts matching this search query.
+    #[arg(long)]
+    pub search: Option<String>,
 
     /// Don't add "_fansly" suffix to creator folders.
     #[arg(long)]
@@ -60,9 +125,9 @@ pub struct Args {
     #[arg(long)]
     pub no_previews: bool,
 
-    /// Hide download progress information.
-    #[arg(long, short)]
-    pub quiet: bool,
+    /// Don't back up the creator profile (avatar, banner, bio, stats).
+    #[arg(long)]
+    pub no_profile: bool,
 
     /// Show information about skipped downloads.
     #[arg(long)]
@@ -73,6 +138,10 @@ pub struct Args {
     #[arg(long)]
     pub use_duplicate_threshold: bool,
 
+    /// Export chat transcripts (JSON and HTML) alongside downloaded messages.
+    #[arg(long)]
+    pub export_transcripts: bool,
+
     /// Number of retry attempts for empty timeline responses.
     #[arg(long)]
     pub timeline_retries: Option<u32>,
@@ -81,9 +150,107 @@ pub struct Args {
     #[arg(long)]
     pub timeline_delay: Option<u64>,
 
-    /// Enable debug logging.
-    #[arg(long)]
-    pub debug: bool,
+    /// Number of creators to process in parallel.
+    #[arg(long, value_name = "N")]
+    pub parallel: Option<usize>,
+
+    /// Total download bandwidth cap, e.g. "8MB/s".
+    #[arg(long, value_name = "RATE")]
+    pub limit_rate: Option<String>,
+}
+
+/// CLI subcommands.
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Download media of the targeted creators (the default command).
+    Download(DownloadArgs),
+    /// List the targeted creators or their timeline walls.
+    List {
+        /// What to list.
+        #[arg(value_enum, default_value_t = ListTarget::Creators)]
+        target: ListTarget,
+    },
+    /// Check downloaded files for truncated videos and corrupt images.
+    Verify {
+        /// Delete broken files so the next download fetches them again.
+        #[arg(long)]
+        delete: bool,
+    },
+    /// Import credentials from a browser HAR or cookies.txt export into the config file.
+    ImportAuth {
+        /// HAR file or Netscape cookies.txt file (format is detected automatically).
+        file: PathBuf,
+    },
+    /// Show or validate the configuration.
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+    /// Keep the token and device ID in a passphrase-encrypted vault.
+    Vault {
+        #[command(subcommand)]
+        action: VaultCommand,
+    },
+    /// Check the configuration and environment and report every problem found.
+    Doctor {
+        /// Skip the WebSocket and API connection checks.
+        #[arg(long)]
+        offline: bool,
+    },
+    /// Download media that failed in previous runs again.
+    RetryFailed,
+    /// Show file counts and sizes of the downloaded creator folders.
+    Stats,
+    /// Stay connected and download new posts, messages and stories of the
+    /// targeted creators as soon as they are announced.
+    Watch {
+        /// Also watch every followed creator that announces new content.
+        #[arg(long)]
+        following: bool,
+    },
+    /// Keep running and sync the targeted creators on a schedule ([daemon] config section).
+    Daemon {
+        /// Default interval between syncs, e.g. "1h" or "30m".
+        #[arg(long, value_name = "DURATION")]
+        interval: Option<String>,
+
+        /// Maximum random delay added to every interval, e.g. "5m".
+        #[arg(long, value_name = "DURATION")]
+        jitter: Option<String>,
+    },
+}
+
+/// What the `list` command lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ListTarget {
+    /// Targeted creators with their account IDs and folders.
+    Creators,
+    /// Timeline walls of each targeted creator.
+    Walls,
+}
+
+/// Actions of the `config` command.
+#[derive(Subcommand, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigCommand {
+    /// Print the effective configuration (file and CLI overrides), token masked.
+    Show,
+    /// Validate the effective configuration without connecting.
+    Validate,
+    /// Print the config file, cache and state locations in use.
+    Path,
+}
+
+/// Actions of the `vault` command.
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum VaultCommand {
+    /// Move the token and device ID out of the config file into a new vault.
+    Init {
+        /// Vault file (default: vault.json in the state directory).
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+    /// Change the vault passphrase.
+    Passwd,
 }
 
 /// CLI download mode argument.
@@ -99,6 +266,8 @@ pub enum DownloadModeArg {
     Single,
     /// Download purchased media collections.
     Collection,
+    /// Download currently active stories.
+    Stories,
 }
 
 impl From<DownloadModeArg> for DownloadMode {
@@ -109,16 +278,39 @@ impl From<DownloadModeArg> for DownloadMode {
             DownloadModeArg::Messages => DownloadMode::Messages,
             DownloadModeArg::Single => DownloadMode::Single,
             DownloadModeArg::Collection => DownloadMode::Collection,
+            DownloadModeArg::Stories => DownloadMode::Stories,
         }
     }
 }
 
 impl Args {
+    /// Get the command to run, resolving the defaults of the flat flag set.
+    pub fn resolved_command(&self) -> Command {
+        match &self.command {
+            Some(command) => command.clone(),
+            None if self.list_walls => Command::List {
+                target: ListTarget::Walls,
+            },
+            None => Command::Download(self.download.clone()),
+        }
+    }
+
+    /// Check the arguments for combinations the config can't express.
+    pub fn validate(&self) -> Result<()> {
+        self.download.validate()?;
+        if let Some(Command::Download(download)) = &self.command {
+            download.validate()?;
+        }
+        Ok(())
+    }
+
     /// Merge CLI arguments into an existing config, overriding where specified.
+    ///
+    /// Options given after the subcommand win over the same options given before it.
     pub fn merge_into_config(self, config: &mut Config) {
         // Override usernames if provided
         if let Some(users) = self.user {
-            config.targeted_creator.usernames = users.into_iter().collect();
+            config.targeted_creator.set_usernames(users);
         }
 
         // Override account settings if provided
@@ -143,12 +335,81 @@ impl Args {
             config.options.download_directory = Some(dir);
         }
 
+        if self.quiet {
+            config.options.show_downloads = false;
+            config.options.show_skipped_downloads = false;
+        }
+
+        if let Some(rps) = self.api_rps {
+            config.options.api_requests_per_second = rps;
+        }
+
+        if let Some(rps) = self.cdn_rps {
+            config.options.cdn_requests_per_second = rps;
+        }
+
+        if let Some(proxy) = self.proxy {
+            config.options.proxy = Some(proxy);
+        }
+
+        self.download.merge_into_config(config);
+
+        match self.command {
+            Some(Command::Download(download)) => download.merge_into_config(config),
+            Some(Command::Daemon { interval, jitter }) => {
+                if let Some(interval) = interval {
+                    config.daemon.interval = interval;
+                }
+                if let Some(jitter) = jitter {
+                    config.daemon.jitter = jitter;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl DownloadArgs {
+    /// Check that `--post`/`--post-file` aren't combined with another mode.
+    pub fn validate(&self) -> Result<()> {
+        let has_posts = self.post.is_some() || self.post_file.is_some();
+        match self.mode {
+            Some(mode) if has_posts && !matches!(mode, DownloadModeArg::Single) => {
+                Err(Error::ConfigValidation {
+                    field: "mode".to_string(),
+                    message: "--post and --post-file can only be used with --mode single"
+                        .to_string(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Merge download options into an existing config, overriding where specified.
+    pub fn merge_into_config(self, config: &mut Config) {
+        // Posts imply single post mode
+        if self.post.is_some() || self.post_file.is_some() {
+            config.options.download_mode = DownloadMode::Single;
+        }
+
         if let Some(mode) = self.mode {
             config.options.download_mode = mode.into();
         }
 
-        if let Some(post) = self.post {
-            config.options.single_post_id = Some(post);
+        if let Some(wall) = self.wall {
+            config.options.timeline_wall = Some(wall);
+        }
+
+        if let Some(search) = self.search {
+            config.options.content_search = Some(search);
+        }
+
+        if let Some(posts) = self.post {
+            config.options.single_post_ids = posts;
+        }
+
+        if let Some(post_file) = self.post_file {
+            config.options.post_file = Some(post_file);
         }
 
         // Boolean flags (only override if set to non-default)
@@ -160,9 +421,8 @@ impl Args {
             config.options.download_media_previews = false;
         }
 
-        if self.quiet {
-            config.options.show_downloads = false;
-            config.options.show_skipped_downloads = false;
+        if self.no_profile {
+            config.options.download_profile = false;
         }
 
         if self.show_skipped {
@@ -173,6 +433,10 @@ impl Args {
             config.options.use_duplicate_threshold = true;
         }
 
+        if self.export_transcripts {
+            config.options.export_transcripts = true;
+        }
+
         if let Some(retries) = self.timeline_retries {
             config.options.timeline_retries = retries;
         }
@@ -180,5 +444,96 @@ impl Args {
         if let Some(delay) = self.timeline_delay {
             config.options.timeline_delay_seconds = delay;
         }
+
+        if let Some(parallel) = self.parallel {
+            config.options.max_concurrent_creators = parallel;
+        }
+
+        if let Some(rate) = self.limit_rate {
+            config.options.max_download_speed = Some(rate);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Args {
+        Args::try_parse_from(std::iter::once("fansly-downloader").chain(args.iter().copied()))
+            .unwrap()
+    }
+
+    fn merged(args: &[&str]) -> Config {
+        let mut config = Config::default();
+        parse(args).merge_into_config(&mut config);
+        config
+    }
+
+    #[test]
+    fn test_flat_flags_still_download() {
+        let args = parse(&["--user", "a", "--mode", "timeline", "--no-previews"]);
+        assert!(matches!(args.resolved_command(), Command::Download(_)));
+
+        let config = merged(&["--user", "a", "--mode", "timeline", "--no-previews"]);
+        assert_eq!(config.options.download_mode, DownloadMode::Timeline);
+        assert!(!config.options.download_media_previews);
+
+        let args = parse(&["--list-walls"]);
+        assert!(matches!(
+            args.resolved_command(),
+            Command::List {
+                target: ListTarget::Walls
+            }
+        ));
+    }
+
+    #[test]
+    fn test_subcommand_args() {
+        let config = merged(&["-d", "/downloads", "download", "--mode", "messages", "-q"]);
+        assert_eq!(config.options.download_mode, DownloadMode::Messages);
+        assert_eq!(
+            config.options.download_directory,
+            Some(PathBuf::from("/downloads"))
+        );
+        assert!(!config.options.show_downloads);
+
+        // Options after the subcommand win
+        let config = merged(&["--mode", "timeline", "download", "--mode", "stories"]);
+        assert_eq!(config.options.download_mode, DownloadMode::Stories);
+
+        let args = parse(&["config", "show"]);
+        assert!(matches!(
+            args.command,
+            Some(Command::Config {
+                action: ConfigCommand::Show
+            })
+        ));
+        assert!(matches!(
+            parse(&["verify", "--delete"]).command,
+            Some(Command::Verify { delete: true })
+        ));
+        assert!(matches!(
+            parse(&["watch", "--following"]).command,
+            Some(Command::Watch { following: true })
+        ));
+    }
+
+    #[test]
+    fn test_posts_imply_single_mode() {
+        let config = merged(&["download", "--post", "1234567890123"]);
+        assert_eq!(config.options.download_mode, DownloadMode::Single);
+
+        assert!(parse(&["--post", "1234567890123"]).validate().is_ok());
+        assert!(
+            parse(&["download", "--post", "1234567890123", "--mode", "single"])
+                .validate()
+                .is_ok()
+        );
+        assert!(
+            parse(&["download", "--post", "1234567890123", "--mode", "timeline"])
+                .validate()
+                .is_err()
+        );
     }
 }
@@ -2,4 +2,6 @@
 
 pub mod args;
 
-pub use args::{Args, DownloadModeArg};
+pub use args::{
+    Args, Command, ConfigCommand, DownloadArgs, DownloadModeArg, ListTarget, VaultCommand,
+};
@@ -0,0 +1,116 @@
+//! Building the API client from the configuration.
+//!
+//! The `api` module knows nothing about the configuration model; this is where
+//! the account, proxy, rate and bandwidth settings are turned into a client.
+
+use crate::api::throttle::{parse_rate, parse_time};
+use crate::api::{BandwidthLimiter, BandwidthSchedule, FanslyApi, ProxyConfig};
+use crate::config::loader::{Config, OptionsConfig};
+use crate::error::{Error, Result};
+
+impl Config {
+    /// Create an API client from the configuration, applying the proxy, rate
+    /// limits and bandwidth limits it specifies.
+    pub async fn build_api(&self) -> Result<FanslyApi> {
+        let proxy = ProxyConfig::resolve(self.options.proxy.as_deref())?;
+        if let Some(proxy) = &proxy {
+            tracing::info!("Using proxy {}", proxy.url());
+        }
+
+        let bandwidth = bandwidth_schedule(&self.options)?;
+
+        let account = &self.my_account;
+        let mut builder = FanslyApi::builder(account.authorization_token.as_str())
+            .user_agent(account.user_agent.as_str())
+            .check_key(account.check_key.as_str());
+        if let Some(device_id) = &self.cache.device_id {
+            builder = builder.device_id(device_id.as_str(), self.cache.device_id_timestamp);
+        }
+        if let Some(proxy) = proxy {
+            builder = builder.proxy(proxy);
+        }
+        let api = builder.build().await?;
+
+        Ok(api
+            .with_rate_limits(
+                self.options.api_requests_per_second,
+                self.options.cdn_requests_per_second,
+            )
+            .with_bandwidth_limit(BandwidthLimiter::new(bandwidth)))
+    }
+}
+
+/// Build the bandwidth schedule from `max_download_speed` and `bandwidth_schedule`.
+pub fn bandwidth_schedule(options: &OptionsConfig) -> Result<BandwidthSchedule> {
+    let default_limit = match &options.max_download_speed {
+        Some(limit) => parse_rate(limit).map_err(|e| invalid("max_download_speed", e))?,
+        None => None,
+    };
+
+    let mut windows = Vec::new();
+    for window in &options.bandwidth_schedule {
+        let start = parse_time(&window.start).map_err(|e| invalid("bandwidth_schedule", e))?;
+        let end = parse_time(&window.end).map_err(|e| invalid("bandwidth_schedule", e))?;
+        let limit = parse_rate(&window.limit).map_err(|e| invalid("bandwidth_schedule", e))?;
+        windows.push((start, end, limit));
+    }
+
+    Ok(BandwidthSchedule::new(default_limit, windows))
+}
+
+fn invalid(field: &str, message: String) -> Error {
+    Error::ConfigValidation {
+        field: field.to_string(),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BandwidthWindow;
+    use chrono::NaiveTime;
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_bandwidth_schedule_from_options() {
+        let options = OptionsConfig {
+            max_download_speed: Some("8MB/s".to_string()),
+            bandwidth_schedule: vec![
+                BandwidthWindow {
+                    start: "23:00".to_string(),
+                    end: "07:00".to_string(),
+                    limit: "unlimited".to_string(),
+                },
+                BandwidthWindow {
+                    start: "12:00".to_string(),
+                    end: "13:00".to_string(),
+                    limit: "1MB/s".to_string(),
+                },
+            ],
+            ..Default::default()
+        };
+        let schedule = bandwidth_schedule(&options).unwrap();
+
+        assert_eq!(schedule.limit_at(time(2, 0)), None);
+        assert_eq!(schedule.limit_at(time(7, 0)), Some(8_000_000));
+        assert_eq!(schedule.limit_at(time(12, 30)), Some(1_000_000));
+    }
+
+    #[test]
+    fn test_bandwidth_schedule_rejects_bad_time() {
+        let options = OptionsConfig {
+            bandwidth_schedule: vec![BandwidthWindow {
+                start: "25:00".to_string(),
+                end: "07:00".to_string(),
+                limit: "1MB".to_string(),
+            }],
+            ..Default::default()
+        };
+        let error = bandwidth_schedule(&options).unwrap_err();
+        assert!(error.to_string().contains("bandwidth_schedule"));
+    }
+}
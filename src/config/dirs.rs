@@ -0,0 +1,47 @@
+//! Standard per-user locations for the config file, cache and state
+//! (XDG base directories on Linux, the platform equivalents elsewhere).
+
+use std::path::{Path, PathBuf};
+
+use directories::ProjectDirs;
+
+/// Config filename, in the working directory or the user config directory.
+pub const CONFIG_FILE: &str = "config.toml";
+
+fn project_dirs() -> Option<ProjectDirs> {
+    ProjectDirs::from("", "", "fansly-downloader")
+}
+
+/// Get the config file in the user config directory,
+/// e.g. `~/.config/fansly-downloader/config.toml`.
+pub fn user_config_file() -> Option<PathBuf> {
+    project_dirs().map(|dirs| dirs.config_dir().join(CONFIG_FILE))
+}
+
+/// Get the user cache directory, e.g. `~/.cache/fansly-downloader`.
+pub fn user_cache_dir() -> Option<PathBuf> {
+    project_dirs().map(|dirs| dirs.cache_dir().to_path_buf())
+}
+
+/// Get the user data directory, e.g. `~/.local/share/fansly-downloader`.
+pub fn user_data_dir() -> Option<PathBuf> {
+    project_dirs().map(|dirs| dirs.data_dir().to_path_buf())
+}
+
+/// Find the config file to use.
+///
+/// An explicit path always wins. Otherwise `config.toml` in the working
+/// directory is used if it exists (as in earlier versions), then the one in
+/// the user config directory.
+pub fn find_config_file(explicit: Option<&Path>) -> PathBuf {
+    if let Some(path) = explicit {
+        return path.to_path_buf();
+    }
+
+    let local = PathBuf::from(CONFIG_FILE);
+    if local.exists() {
+        return local;
+    }
+
+    user_config_file().unwrap_or(local)
+}
@@ -0,0 +1,210 @@
+//! Format-preserving updates of the configuration file.
+//!
+//! Only the edited keys change; comments, key order and all other values are
+//! written back exactly as they were in the file.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use toml_edit::{DocumentMut, Item, Table, TableLike, Value};
+
+use crate::config::vault::write_private;
+use crate::error::{Error, Result};
+
+/// An open configuration file being edited.
+#[derive(Debug)]
+pub struct ConfigEditor {
+    document: DocumentMut,
+    path: PathBuf,
+}
+
+impl ConfigEditor {
+    /// Open a configuration file, or start an empty one if it doesn't exist.
+    pub fn open(path: &Path) -> Result<Self> {
+        let content = if path.exists() {
+            fs::read_to_string(path)?
+        } else {
+            String::new()
+        };
+
+        let document = content
+            .parse::<DocumentMut>()
+            .map_err(|e| Error::Config(format!("Failed to parse {}: {}", path.display(), e)))?;
+
+        Ok(Self {
+            document,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Set a key in a section, inside `[profiles.<name>]` if a profile is given.
+    pub fn set(
+        &mut self,
+        profile: Option<&str>,
+        section: &str,
+        key: &str,
+        value: impl Into<Value>,
+    ) -> Result<()> {
+        let table = self.section_mut(profile, section)?;
+        match table.get_mut(key).and_then(Item::as_value_mut) {
+            // Keep the comments and spacing around the old value
+            Some(existing) => {
+                let decor = existing.decor().clone();
+                *existing = value.into();
+                *existing.decor_mut() = decor;
+            }
+            None => {
+                table.insert(key, Item::Value(value.into()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove a key from a section, inside `[profiles.<name>]` if a profile is given.
+    pub fn remove(&mut self, profile: Option<&str>, section: &str, key: &str) -> Result<()> {
+        let mut item = self.document.as_item_mut();
+        let path = match profile {
+            Some(name) => vec!["profiles", name, section],
+            None => vec![section],
+        };
+        for key in path {
+            match item
+                .as_table_like_mut()
+                .and_then(|table| table.get_mut(key))
+            {
+                Some(next) => item = next,
+                // Nothing to remove; don't create the section
+                None => return Ok(()),
+            }
+        }
+
+        if let Some(table) = item.as_table_like_mut() {
+            table.remove(key);
+        }
+        Ok(())
+    }
+
+    /// Write the file back, readable only by the user as it may hold the token.
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        write_private(&self.path, self.document.to_string().as_bytes())
+    }
+
+    /// Get a section table, creating it (and its parents) if needed.
+    fn section_mut(&mut self, profile: Option<&str>, section: &str) -> Result<&mut dyn TableLike> {
+        let path: Vec<&str> = match profile {
+            Some(name) => vec!["profiles", name, section],
+            None => vec![section],
+        };
+
+        let mut table: &mut dyn TableLike = self.document.as_table_mut();
+        for (i, key) in path.iter().enumerate() {
+            let is_parent = i + 1 < path.len();
+            let item = table.entry(key).or_insert_with(|| {
+                let mut new = Table::new();
+                new.set_implicit(is_parent);
+                Item::Table(new)
+            });
+            table = item.as_table_like_mut().ok_or_else(|| {
+                Error::Config(format!(
+                    "'{}' in the config file is not a table",
+                    path[..=i].join(".")
+                ))
+            })?;
+        }
+
+        Ok(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"# My config
+[my_account]
+# Keep this secret
+authorization_token = "token"
+
+[options]
+download_mode = "timeline" # only posts
+
+[cache]
+device_id = "old" # from the browser
+"#;
+
+    #[test]
+    fn test_set_preserves_formatting() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, CONFIG).unwrap();
+
+        let mut editor = ConfigEditor::open(&path).unwrap();
+        editor.set(None, "cache", "device_id", "new").unwrap();
+        editor
+            .set(None, "cache", "device_id_timestamp", 1234i64)
+            .unwrap();
+        editor.save().unwrap();
+
+        let expected = CONFIG.replace(
+            "device_id = \"old\" # from the browser\n",
+            "device_id = \"new\" # from the browser\ndevice_id_timestamp = 1234\n",
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_set_in_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, CONFIG).unwrap();
+
+        let mut editor = ConfigEditor::open(&path).unwrap();
+        editor
+            .set(Some("work"), "cache", "device_id", "work")
+            .unwrap();
+        editor.save().unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with(CONFIG));
+        assert!(content.ends_with("[profiles.work.cache]\ndevice_id = \"work\"\n"));
+    }
+
+    #[test]
+    fn test_save_new_file_is_private() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fansly/config.toml");
+
+        let mut editor = ConfigEditor::open(&path).unwrap();
+        editor
+            .set(None, "my_account", "authorization_token", "secret")
+            .unwrap();
+        editor.save().unwrap();
+
+        assert!(fs::read_to_string(&path).unwrap().contains("secret"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_remove_keeps_missing_sections_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, CONFIG).unwrap();
+
+        let mut editor = ConfigEditor::open(&path).unwrap();
+        editor.remove(None, "cache", "device_id").unwrap();
+        editor.remove(Some("work"), "cache", "device_id").unwrap();
+        editor.save().unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("device_id"));
+        assert!(!content.contains("profiles"));
+    }
+}
@@ -0,0 +1,340 @@
+//! Configuration overrides from `FANSLY_*` environment variables.
+//!
+//! Every `[options]` key can be set with `FANSLY_<KEY>` (e.g.
+//! `FANSLY_DOWNLOAD_MODE=timeline`), the creators with `FANSLY_CREATORS`
+//! (comma-separated) and per-creator options with
+//! `FANSLY_CREATORS__<username>__<KEY>`. Values are TOML (`true`, `3`,
+//! `["a", "b"]`) or plain strings.
+//!
+//! Precedence, highest first: command line, environment, config file, defaults.
+//! [`Config::prepare`] applies the overrides.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::config::loader::Config;
+use crate::config::vault::{NEW_PASSPHRASE_ENV, PASSPHRASE_ENV};
+use crate::error::{Error, Result};
+
+/// Prefix of all configuration variables.
+pub const ENV_PREFIX: &str = "FANSLY_";
+
+/// Variable holding the creator usernames.
+pub const CREATORS_VAR: &str = "FANSLY_CREATORS";
+
+/// Separator between the parts of per-creator variable names.
+const CREATOR_SEPARATOR: &str = "__";
+
+/// `[options]` keys that can be overridden.
+const OPTION_KEYS: &[&str] = &[
+    "download_mode",
+    "download_directory",
+    "download_media_previews",
+    "separate_messages",
+    "separate_timeline",
+    "separate_previews",
+    "use_folder_suffix",
+    "folder_naming",
+    "on_rename",
+    "state_directory",
+    "cache_directory",
+    "show_downloads",
+    "show_skipped_downloads",
+    "use_duplicate_threshold",
+    "timeline_retries",
+    "timeline_delay_seconds",
+    "timeline_wall",
+    "content_search",
+    "single_post_ids",
+    "post_file",
+    "download_profile",
+    "export_transcripts",
+    "max_concurrent_creators",
+    "api_requests_per_second",
+    "cdn_requests_per_second",
+    "max_download_speed",
+    "proxy",
+    "bandwidth_schedule",
+    "since",
+    "max_height",
+];
+
+/// Keys whose plain string values are comma-separated lists.
+const LIST_KEYS: &[&str] = &["single_post_ids"];
+
+/// Variables with the prefix that are read elsewhere (command line and vault).
+const OTHER_VARS: &[&str] = &[
+    "FANSLY_TOKEN",
+    "FANSLY_USER_AGENT",
+    "FANSLY_CHECK_KEY",
+    "FANSLY_DEVICE_ID",
+    "FANSLY_CONFIG",
+    "FANSLY_PROFILE",
+    PASSPHRASE_ENV,
+    NEW_PASSPHRASE_ENV,
+];
+
+/// Apply overrides from `(name, value)` pairs.
+///
+/// Variables without the prefix are ignored; a prefixed variable that is not
+/// an option, or an override of a creator that isn't targeted, is an error.
+///
+/// Called through [`Config::prepare`], after the profile is selected.
+pub(crate) fn apply_overrides<I>(config: &mut Config, vars: I) -> Result<()>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    let mut vars: Vec<(String, String)> = vars
+        .into_iter()
+        .filter(|(name, _)| name.starts_with(ENV_PREFIX) && !OTHER_VARS.contains(&name.as_str()))
+        .collect();
+    // Usernames first, so per-creator options apply to the new list
+    vars.sort_by_key(|(name, _)| name != CREATORS_VAR);
+
+    for (name, value) in vars {
+        let key = &name[ENV_PREFIX.len()..];
+
+        if name == CREATORS_VAR {
+            config.targeted_creator.set_usernames(split_list(&value));
+        } else if let Some(rest) = key.strip_prefix("CREATORS__") {
+            let (username, key) = rest.rsplit_once(CREATOR_SEPARATOR).ok_or_else(|| {
+                Error::Config(format!(
+                    "{}: expected FANSLY_CREATORS__<username>__<key>",
+                    name
+                ))
+            })?;
+            let options = config
+                .targeted_creator
+                .creators
+                .iter_mut()
+                .find(|(existing, _)| {
+                    existing
+                        .trim_start_matches('@')
+                        .eq_ignore_ascii_case(username)
+                })
+                .map(|(_, options)| options)
+                .ok_or_else(|| Error::ConfigValidation {
+                    field: name.clone(),
+                    message: format!(
+                        "{} is not a targeted creator (add it to the creators or {})",
+                        username, CREATORS_VAR
+                    ),
+                })?;
+            *options = with_override(options, &name, &key.to_lowercase(), &value)?;
+        } else {
+            let key = key.to_lowercase();
+            if !OPTION_KEYS.contains(&key.as_str()) {
+                return Err(Error::ConfigValidation {
+                    field: name,
+                    message: "Unknown option".to_string(),
+                });
+            }
+            config.options = with_override(&config.options, &name, &key, &value)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Get a copy of a section with one key replaced by a variable's value.
+fn with_override<T>(section: &T, name: &str, key: &str, value: &str) -> Result<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let table =
+        toml::Table::try_from(section).map_err(|e| Error::Config(format!("{}: {}", name, e)))?;
+
+    let mut candidates = Vec::new();
+    if let Some(parsed) = parse_value(value) {
+        candidates.push(parsed);
+    }
+    candidates.push(if LIST_KEYS.contains(&key) {
+        toml::Value::Array(split_list(value).into_iter().map(Into::into).collect())
+    } else {
+        toml::Value::String(value.to_string())
+    });
+
+    // A value like `123` may be meant as a string (e.g. a wall ID)
+    let mut error = None;
+    for candidate in candidates {
+        let mut table = table.clone();
+        table.insert(key.to_string(), candidate);
+        match table.try_into() {
+            Ok(section) => return Ok(section),
+            Err(e) => error = error.or(Some(e)),
+        }
+    }
+
+    Err(Error::ConfigValidation {
+        field: name.to_string(),
+        message: error.map(|e| e.message().to_string()).unwrap_or_default(),
+    })
+}
+
+/// Parse a variable's value as a TOML value, if it is one.
+fn parse_value(value: &str) -> Option<toml::Value> {
+    let mut table: toml::Table = toml::from_str(&format!("value = {}", value)).ok()?;
+    table.remove("value")
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    if let Some(toml::Value::Array(items)) = parse_value(value) {
+        return items
+            .into_iter()
+            .filter_map(|item| item.as_str().map(str::to_string))
+            .collect();
+    }
+
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Args;
+    use crate::config::{DownloadMode, OptionsConfig};
+    use clap::Parser;
+    use std::path::PathBuf;
+
+    const CONFIG: &str = r#"
+[my_account]
+authorization_token = "token"
+
+[creators]
+usernames = ["alice"]
+
+[creators.Bob]
+download_mode = "timeline"
+
+[options]
+download_mode = "messages"
+timeline_retries = 5
+"#;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_option_keys_are_complete() {
+        let options = OptionsConfig {
+            download_directory: Some(".".into()),
+            state_directory: Some(".".into()),
+            cache_directory: Some(".".into()),
+            timeline_wall: Some(String::new()),
+            content_search: Some(String::new()),
+            post_file: Some(".".into()),
+            max_download_speed: Some(String::new()),
+            proxy: Some(String::new()),
+            since: Some(String::new()),
+            max_height: Some(1),
+            ..Default::default()
+        };
+        let table = toml::Table::try_from(options).unwrap();
+        let mut keys: Vec<&str> = table.keys().map(String::as_str).collect();
+        let mut expected = OPTION_KEYS.to_vec();
+        keys.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(keys, expected);
+    }
+
+    #[test]
+    fn test_env_overrides_options() {
+        let mut config: Config = toml::from_str(CONFIG).unwrap();
+        apply_overrides(
+            &mut config,
+            vars(&[
+                ("FANSLY_DOWNLOAD_MODE", "timeline"),
+                ("FANSLY_DOWNLOAD_DIRECTORY", "/data/fansly"),
+                ("FANSLY_SHOW_DOWNLOADS", "false"),
+                ("FANSLY_API_REQUESTS_PER_SECOND", "0.5"),
+                ("FANSLY_TIMELINE_WALL", "123456"),
+                ("FANSLY_SINGLE_POST_IDS", "111, 222"),
+                ("FANSLY_TOKEN", "read elsewhere"),
+                ("HOME", "/root"),
+            ]),
+        )
+        .unwrap();
+
+        let options = &config.options;
+        assert_eq!(options.download_mode, DownloadMode::Timeline);
+        assert_eq!(
+            options.download_directory,
+            Some(PathBuf::from("/data/fansly"))
+        );
+        assert!(!options.show_downloads);
+        assert_eq!(options.api_requests_per_second, 0.5);
+        assert_eq!(options.timeline_wall.as_deref(), Some("123456"));
+        assert_eq!(options.single_post_ids, vec!["111", "222"]);
+        // Untouched values keep the file's
+        assert_eq!(options.timeline_retries, 5);
+
+        let error =
+            apply_overrides(&mut config, vars(&[("FANSLY_TIMELINE_RETRIES", "many")])).unwrap_err();
+        assert!(error.to_string().contains("FANSLY_TIMELINE_RETRIES"));
+
+        let error =
+            apply_overrides(&mut config, vars(&[("FANSLY_DOWNLOAD_MOD", "timeline")])).unwrap_err();
+        assert!(error.to_string().contains("FANSLY_DOWNLOAD_MOD"));
+    }
+
+    #[test]
+    fn test_env_overrides_creators() {
+        let mut config: Config = toml::from_str(CONFIG).unwrap();
+        apply_overrides(
+            &mut config,
+            vars(&[
+                ("FANSLY_CREATORS__bob__SINCE", "2024-01-01"),
+                ("FANSLY_CREATORS", "carol, bob"),
+                ("FANSLY_CREATORS__carol__MAX_HEIGHT", "720"),
+            ]),
+        )
+        .unwrap();
+
+        let creators = &config.targeted_creator;
+        assert_eq!(creators.usernames(), vec!["bob", "carol"]);
+        let bob = creators.get("bob").unwrap();
+        assert_eq!(bob.download_mode, Some(DownloadMode::Timeline));
+        assert_eq!(bob.since.as_deref(), Some("2024-01-01"));
+        assert_eq!(creators.get("carol").unwrap().max_height, Some(720));
+
+        let error = apply_overrides(&mut config, vars(&[("FANSLY_CREATORS__bob__SPEED", "1")]));
+        assert!(error.is_err());
+
+        // Overrides don't add creators
+        let error = apply_overrides(
+            &mut config,
+            vars(&[("FANSLY_CREATORS__dave__SINCE", "2024-01-01")]),
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("dave"));
+        assert!(config.targeted_creator.get("dave").is_none());
+    }
+
+    #[test]
+    fn test_precedence_cli_over_env_over_file() {
+        let mut config: Config = toml::from_str(CONFIG).unwrap();
+        apply_overrides(
+            &mut config,
+            vars(&[
+                ("FANSLY_DOWNLOAD_MODE", "timeline"),
+                ("FANSLY_TIMELINE_RETRIES", "2"),
+            ]),
+        )
+        .unwrap();
+
+        let args = Args::try_parse_from(["fansly-downloader", "--mode", "collection"]).unwrap();
+        args.merge_into_config(&mut config);
+
+        assert_eq!(config.options.download_mode, DownloadMode::Collection);
+        assert_eq!(config.options.timeline_retries, 2);
+    }
+}
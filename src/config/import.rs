@@ -0,0 +1,278 @@
+//! Import credentials from browser exports.
+//!
+//! Supports HAR files (DevTools → Network → "Save all as HAR") and Netscape
+//! `cookies.txt` files (as written by cookie export extensions). HAR files
+//! carry the authorization token, user agent and device ID; cookie files only
+//! carry the device ID (`fansly-d` cookie).
+
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::config::loader::{AccountConfig, CacheConfig, Config};
+use crate::error::{Error, Result};
+
+/// Name of the cookie holding the device ID.
+const DEVICE_ID_COOKIE: &str = "fansly-d";
+
+/// Credentials found in a browser export.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportedAuth {
+    pub authorization_token: Option<String>,
+    pub user_agent: Option<String>,
+    pub device_id: Option<String>,
+}
+
+impl ImportedAuth {
+    /// Check whether nothing was found.
+    pub fn is_empty(&self) -> bool {
+        self.authorization_token.is_none() && self.user_agent.is_none() && self.device_id.is_none()
+    }
+
+    /// Write the found values into a configuration, keeping the others.
+    pub fn apply_to(&self, config: &mut Config) {
+        self.apply_to_account(&mut config.my_account, &mut config.cache);
+    }
+
+    /// Apply the found values to an account and its cache (e.g. of a profile).
+    pub fn apply_to_account(&self, account: &mut AccountConfig, cache: &mut CacheConfig) {
+        if let Some(token) = &self.authorization_token {
+            account.authorization_token = token.clone();
+        }
+        if let Some(user_agent) = &self.user_agent {
+            account.user_agent = user_agent.clone();
+        }
+        if let Some(device_id) = &self.device_id {
+            if cache.device_id.as_ref() != Some(device_id) {
+                cache.device_id = Some(device_id.clone());
+                // Treated as fresh on the next run
+                cache.device_id_timestamp = None;
+            }
+        }
+    }
+}
+
+/// Read credentials from a HAR or cookies.txt file (detected by content).
+pub fn import_auth_file(path: &Path) -> Result<ImportedAuth> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| Error::Config(format!("Failed to read {}: {}", path.display(), e)))?;
+
+    let imported = if content.trim_start().starts_with('{') {
+        parse_har(&content)?
+    } else {
+        parse_cookies_txt(&content)
+    };
+
+    if imported.is_empty() {
+        return Err(Error::Config(format!(
+            "No Fansly credentials found in {}",
+            path.display()
+        )));
+    }
+
+    Ok(imported)
+}
+
+#[derive(Deserialize)]
+struct Har {
+    log: HarLog,
+}
+
+#[derive(Deserialize)]
+struct HarLog {
+    #[serde(default)]
+    entries: Vec<HarEntry>,
+}
+
+#[derive(Deserialize)]
+struct HarEntry {
+    request: HarRequest,
+}
+
+#[derive(Deserialize)]
+struct HarRequest {
+    url: String,
+    #[serde(default)]
+    headers: Vec<HarPair>,
+    #[serde(default)]
+    cookies: Vec<HarPair>,
+}
+
+#[derive(Deserialize)]
+struct HarPair {
+    name: String,
+    value: String,
+}
+
+/// Extract credentials from the Fansly API requests in a HAR file.
+///
+/// Later requests win, so the most recent session is imported.
+pub fn parse_har(content: &str) -> Result<ImportedAuth> {
+    let har: Har = serde_json::from_str(content)
+        .map_err(|e| Error::Config(format!("Invalid HAR file: {}", e)))?;
+
+    let mut imported = ImportedAuth::default();
+    let mut cookie_device_id = None;
+
+    for entry in &har.log.entries {
+        let request = &entry.request;
+        if !is_fansly_url(&request.url) {
+            continue;
+        }
+
+        for header in &request.headers {
+            let value = header.value.trim();
+            if value.is_empty() {
+                continue;
+            }
+
+            match header.name.to_ascii_lowercase().as_str() {
+                "authorization" => imported.authorization_token = Some(value.to_string()),
+                "user-agent" => imported.user_agent = Some(value.to_string()),
+                "fansly-client-id" => imported.device_id = Some(value.to_string()),
+                "cookie" => {
+                    if let Some(device_id) = cookie_header_value(value, DEVICE_ID_COOKIE) {
+                        cookie_device_id = Some(device_id);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for cookie in &request.cookies {
+            if cookie.name == DEVICE_ID_COOKIE && !cookie.value.is_empty() {
+                cookie_device_id = Some(cookie.value.clone());
+            }
+        }
+    }
+
+    // The client ID header is what the API actually checks; fall back to the cookie
+    if imported.device_id.is_none() {
+        imported.device_id = cookie_device_id;
+    }
+
+    Ok(imported)
+}
+
+/// Extract the device ID from a Netscape cookies.txt file.
+pub fn parse_cookies_txt(content: &str) -> ImportedAuth {
+    let mut imported = ImportedAuth::default();
+
+    for line in content.lines() {
+        // Cookie files mark HttpOnly cookies with a prefix on an otherwise commented line
+        let line = line.strip_prefix("#HttpOnly_").unwrap_or(line);
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 7 {
+            continue;
+        }
+
+        let (domain, name, value) = (fields[0], fields[5], fields[6].trim());
+        if domain.trim_start_matches('.').ends_with("fansly.com")
+            && name == DEVICE_ID_COOKIE
+            && !value.is_empty()
+        {
+            imported.device_id = Some(value.to_string());
+        }
+    }
+
+    imported
+}
+
+/// Check whether a request URL targets Fansly.
+fn is_fansly_url(url: &str) -> bool {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| {
+            u.host_str()
+                .map(|h| h == "fansly.com" || h.ends_with(".fansly.com"))
+        })
+        .unwrap_or(false)
+}
+
+/// Get a cookie value from a `Cookie` header.
+fn cookie_header_value(header: &str, name: &str) -> Option<String> {
+    header.split(';').find_map(|pair| {
+        let (key, value) = pair.trim().split_once('=')?;
+        (key == name && !value.is_empty()).then(|| value.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HAR: &str = r#"{
+        "log": {
+            "entries": [
+                {
+                    "request": {
+                        "url": "https://example.com/api",
+                        "headers": [{"name": "Authorization", "value": "not-fansly"}]
+                    }
+                },
+                {
+                    "request": {
+                        "url": "https://apiv3.fansly.com/api/v1/account/me?ngsw-bypass=true",
+                        "headers": [
+                            {"name": "authorization", "value": "TOKEN123"},
+                            {"name": "User-Agent", "value": "Mozilla/5.0 Test"},
+                            {"name": "Cookie", "value": "a=b; fansly-d=111"}
+                        ],
+                        "cookies": []
+                    }
+                }
+            ]
+        }
+    }"#;
+
+    #[test]
+    fn test_parse_har() {
+        let imported = parse_har(HAR).unwrap();
+        assert_eq!(imported.authorization_token.as_deref(), Some("TOKEN123"));
+        assert_eq!(imported.user_agent.as_deref(), Some("Mozilla/5.0 Test"));
+        // No client ID header, so the cookie is used
+        assert_eq!(imported.device_id.as_deref(), Some("111"));
+    }
+
+    #[test]
+    fn test_parse_har_prefers_client_id_header() {
+        let har = HAR.replace(
+            r#"{"name": "Cookie", "value": "a=b; fansly-d=111"}"#,
+            r#"{"name": "fansly-client-id", "value": "222"}"#,
+        );
+        let imported = parse_har(&har).unwrap();
+        assert_eq!(imported.device_id.as_deref(), Some("222"));
+    }
+
+    #[test]
+    fn test_parse_cookies_txt() {
+        let content = "# Netscape HTTP Cookie File\n\
+                       .example.com\tTRUE\t/\tTRUE\t0\tfansly-d\twrong\n\
+                       #HttpOnly_.fansly.com\tTRUE\t/\tTRUE\t1999999999\tfansly-d\t333\n";
+        let imported = parse_cookies_txt(content);
+        assert_eq!(imported.device_id.as_deref(), Some("333"));
+        assert!(imported.authorization_token.is_none());
+    }
+
+    #[test]
+    fn test_apply_to_keeps_other_values() {
+        let mut config = Config::default();
+        config.my_account.authorization_token = "old".to_string();
+        config.cache.device_id_timestamp = Some(1);
+
+        let imported = ImportedAuth {
+            device_id: Some("444".to_string()),
+            ..Default::default()
+        };
+        imported.apply_to(&mut config);
+
+        assert_eq!(config.my_account.authorization_token, "old");
+        assert_eq!(config.cache.device_id.as_deref(), Some("444"));
+        assert_eq!(config.cache.device_id_timestamp, None);
+    }
+}
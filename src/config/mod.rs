@@ -4,11 +4,29 @@
 //! - Loading configuration from TOML files
 //! - CLI argument parsing and merging
 //! - Configuration validation
+//! - Importing credentials from browser exports
+//! - Standard config, cache and state locations
+//! - Encrypted storage of credentials
+//! - Overrides from `FANSLY_*` environment variables
+//! - Building the API client from the settings
 
+pub mod api;
+pub mod dirs;
+pub mod edit;
+pub mod env;
+pub mod import;
 pub mod loader;
 pub mod modes;
 pub mod validation;
+pub mod vault;
 
-pub use loader::{AccountConfig, CacheConfig, Config, CreatorConfig, OptionsConfig};
+pub use dirs::find_config_file;
+pub use edit::ConfigEditor;
+pub use import::{import_auth_file, ImportedAuth};
+pub use loader::{
+    parse_since, AccountConfig, BandwidthWindow, CacheConfig, Config, CreatorConfig,
+    CreatorOptions, DaemonConfig, FolderNaming, OptionsConfig, ProfileConfig, RenameAction,
+};
 pub use modes::{DownloadMode, DownloadType};
-pub use validation::{parse_post_id, validate_config};
+pub use validation::{parse_post_id, validate_account, validate_config, validate_options};
+pub use vault::Vault;
@@ -8,7 +8,7 @@ use std::str::FromStr;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum DownloadMode {
-    /// Download both timeline and messages (default).
+    /// Download timeline, messages, collections and stories (default).
     #[default]
     Normal,
     /// Download only timeline posts.
@@ -19,6 +19,8 @@ pub enum DownloadMode {
     Single,
     /// Download purchased media collections.
     Collection,
+    /// Download currently active stories.
+    Stories,
 }
 
 impl fmt::Display for DownloadMode {
@@ -29,6 +31,7 @@ impl fmt::Display for DownloadMode {
             DownloadMode::Messages => write!(f, "messages"),
             DownloadMode::Single => write!(f, "single"),
             DownloadMode::Collection => write!(f, "collection"),
+            DownloadMode::Stories => write!(f, "stories"),
         }
     }
 }
@@ -43,6 +46,7 @@ impl FromStr for DownloadMode {
             "messages" => Ok(DownloadMode::Messages),
             "single" => Ok(DownloadMode::Single),
             "collection" => Ok(DownloadMode::Collection),
+            "stories" => Ok(DownloadMode::Stories),
             _ => Err(format!("Unknown download mode: {}", s)),
         }
     }
@@ -57,6 +61,7 @@ pub enum DownloadType {
     Messages,
     Single,
     Collections,
+    Stories,
 }
 
 impl fmt::Display for DownloadType {
@@ -67,6 +72,7 @@ impl fmt::Display for DownloadType {
             DownloadType::Messages => write!(f, "messages"),
             DownloadType::Single => write!(f, "single"),
             DownloadType::Collections => write!(f, "collections"),
+            DownloadType::Stories => write!(f, "stories"),
         }
     }
 }
@@ -1,6 +1,7 @@
 //! Configuration validation logic.
 
-use crate::config::loader::Config;
+use crate::config::loader::{parse_since, Config};
+use crate::config::modes::DownloadMode;
 use crate::error::{Error, Result};
 use regex::Regex;
 
@@ -18,10 +19,98 @@ const MAX_USERNAME_LENGTH: usize = 30;
 
 /// Validate the entire configuration.
 pub fn validate_config(config: &Config) -> Result<()> {
+    validate_account(config)?;
+
+    // Single post mode derives creators from the posts themselves
+    let usernames = config.targeted_creator.usernames();
+    if config.options.download_mode != DownloadMode::Single || !usernames.is_empty() {
+        validate_usernames(&usernames)?;
+    }
+    validate_options(config)
+}
+
+/// Validate the download options and per-creator overrides.
+pub fn validate_options(config: &Config) -> Result<()> {
+    validate_creator_options(config)?;
+
+    if config.options.max_concurrent_creators == 0 {
+        return Err(Error::ConfigValidation {
+            field: "max_concurrent_creators".to_string(),
+            message: "Must be at least 1".to_string(),
+        });
+    }
+
+    validate_rate(
+        "api_requests_per_second",
+        config.options.api_requests_per_second,
+    )?;
+    validate_rate(
+        "cdn_requests_per_second",
+        config.options.cdn_requests_per_second,
+    )?;
+
+    Ok(())
+}
+
+/// Validate the account credentials only (token, user agent, check key).
+pub fn validate_account(config: &Config) -> Result<()> {
     validate_token(&config.my_account.authorization_token)?;
     validate_user_agent(&config.my_account.user_agent)?;
-    validate_check_key(&config.my_account.check_key)?;
-    validate_usernames(&config.targeted_creator.usernames)?;
+    validate_check_key(&config.my_account.check_key)
+}
+
+/// Validate the date floor, quality cap and per-creator overrides.
+fn validate_creator_options(config: &Config) -> Result<()> {
+    config.options.since_timestamp()?;
+    validate_max_height("max_height", config.options.max_height)?;
+
+    for (username, creator) in &config.targeted_creator.creators {
+        if creator.download_mode == Some(DownloadMode::Single) {
+            return Err(Error::ConfigValidation {
+                field: format!("creators.{}.download_mode", username),
+                message: "Single post mode can't be set per creator".to_string(),
+            });
+        }
+
+        if let Some(since) = &creator.since {
+            parse_since(since).map_err(|e| match e {
+                Error::ConfigValidation { message, .. } => Error::ConfigValidation {
+                    field: format!("creators.{}.since", username),
+                    message,
+                },
+                e => e,
+            })?;
+        }
+
+        validate_max_height(
+            &format!("creators.{}.max_height", username),
+            creator.max_height,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Validate a maximum video height (must be positive if set).
+fn validate_max_height(field: &str, max_height: Option<u32>) -> Result<()> {
+    if max_height == Some(0) {
+        return Err(Error::ConfigValidation {
+            field: field.to_string(),
+            message: "Must be greater than zero".to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Validate a requests-per-second limit (0 disables the limit).
+fn validate_rate(field: &str, rate: f64) -> Result<()> {
+    if !rate.is_finite() || rate < 0.0 {
+        return Err(Error::ConfigValidation {
+            field: field.to_string(),
+            message: format!("Must be a non-negative number (got {})", rate),
+        });
+    }
 
     Ok(())
 }
@@ -234,4 +323,29 @@ mod tests {
         assert!(parse_post_id("12345").is_err()); // Too short
         assert!(parse_post_id("not-a-number").is_err());
     }
+
+    #[test]
+    fn test_zero_concurrent_creators_rejected() {
+        let mut config = Config::default();
+        config.my_account.authorization_token = "a".repeat(MIN_TOKEN_LENGTH);
+        config.my_account.user_agent = "Mozilla/5.0 (X11; Linux x86_64) Gecko/20100101".to_string();
+        config
+            .targeted_creator
+            .set_usernames(["creator1".to_string()]);
+        assert!(validate_config(&config).is_ok());
+
+        config.options.max_concurrent_creators = 0;
+        assert!(matches!(
+            validate_config(&config),
+            Err(Error::ConfigValidation { field, .. }) if field == "max_concurrent_creators"
+        ));
+    }
+
+    #[test]
+    fn test_validate_rate() {
+        assert!(validate_rate("rate", 0.0).is_ok());
+        assert!(validate_rate("rate", 2.5).is_ok());
+        assert!(validate_rate("rate", -1.0).is_err());
+        assert!(validate_rate("rate", f64::NAN).is_err());
+    }
 }
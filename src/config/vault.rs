@@ -0,0 +1,333 @@
+//! Passphrase-encrypted storage for the authorization token and device ID.
+//!
+//! The vault is a JSON file holding the secrets encrypted with
+//! ChaCha20-Poly1305, under a key derived from the passphrase with
+//! PBKDF2-HMAC-SHA256. The passphrase is read from `FANSLY_VAULT_PASSPHRASE`
+//! or asked for on the terminal.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::num::NonZeroU32;
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// Default vault filename inside the state directory.
+pub const VAULT_FILE: &str = "vault.json";
+
+/// Environment variable holding the vault passphrase.
+pub const PASSPHRASE_ENV: &str = "FANSLY_VAULT_PASSPHRASE";
+
+/// Environment variable holding a new passphrase (`vault init` and `vault passwd`).
+pub const NEW_PASSPHRASE_ENV: &str = "FANSLY_VAULT_NEW_PASSPHRASE";
+
+/// Vault entry names.
+pub const TOKEN_KEY: &str = "authorization_token";
+pub const DEVICE_ID_KEY: &str = "device_id";
+pub const DEVICE_ID_TIMESTAMP_KEY: &str = "device_id_timestamp";
+
+const VERSION: u32 = 1;
+const ITERATIONS: u32 = 600_000;
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+
+/// On-disk format of the vault.
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    iterations: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// An unlocked vault.
+#[derive(Clone)]
+pub struct Vault {
+    path: PathBuf,
+    iterations: u32,
+    salt: [u8; SALT_LEN],
+    key: [u8; KEY_LEN],
+    secrets: BTreeMap<String, String>,
+}
+
+impl fmt::Debug for Vault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Vault")
+            .field("path", &self.path)
+            .field("entries", &self.secrets.keys().collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Vault {
+    /// Create an empty vault protected by a passphrase (not written until saved).
+    pub fn create(path: &Path, passphrase: &str) -> Result<Self> {
+        Self::create_with_iterations(path, passphrase, ITERATIONS)
+    }
+
+    pub(crate) fn create_with_iterations(
+        path: &Path,
+        passphrase: &str,
+        iterations: u32,
+    ) -> Result<Self> {
+        if passphrase.is_empty() {
+            return Err(Error::Vault("Passphrase must not be empty".to_string()));
+        }
+
+        let mut salt = [0u8; SALT_LEN];
+        fill_random(&mut salt)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            iterations,
+            salt,
+            key: derive_key(passphrase, &salt, iterations)?,
+            secrets: BTreeMap::new(),
+        })
+    }
+
+    /// Open and decrypt a vault file.
+    pub fn unlock(path: &Path, passphrase: &str) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| Error::Vault(format!("Failed to read vault {}: {}", path.display(), e)))?;
+        let file: VaultFile = serde_json::from_str(&content)?;
+        if file.version != VERSION {
+            return Err(Error::Vault(format!(
+                "Unsupported vault version {}",
+                file.version
+            )));
+        }
+
+        let salt: [u8; SALT_LEN] = decode(&file.salt, "salt")?;
+        let nonce: [u8; NONCE_LEN] = decode(&file.nonce, "nonce")?;
+        let mut data = STANDARD
+            .decode(&file.ciphertext)
+            .map_err(|_| Error::Vault("Invalid vault ciphertext".to_string()))?;
+
+        let key = derive_key(passphrase, &salt, file.iterations)?;
+        let plaintext = sealing_key(&key)?
+            .open_in_place(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+            .map_err(|_| {
+                Error::Vault(format!(
+                    "Wrong passphrase or damaged vault {}",
+                    path.display()
+                ))
+            })?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            iterations: file.iterations,
+            salt,
+            key,
+            secrets: serde_json::from_slice(plaintext)?,
+        })
+    }
+
+    /// Change the passphrase (written on the next save).
+    pub fn set_passphrase(&mut self, passphrase: &str) -> Result<()> {
+        let vault = Self::create_with_iterations(&self.path, passphrase, self.iterations)?;
+        self.salt = vault.salt;
+        self.key = vault.key;
+        Ok(())
+    }
+
+    /// Get the vault file path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get a secret.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.secrets.get(name).map(String::as_str)
+    }
+
+    /// Set a secret (written on the next save).
+    pub fn set(&mut self, name: &str, value: impl Into<String>) {
+        self.secrets.insert(name.to_string(), value.into());
+    }
+
+    /// Encrypt and write the vault, with a fresh nonce.
+    pub fn save(&self) -> Result<()> {
+        let mut nonce = [0u8; NONCE_LEN];
+        fill_random(&mut nonce)?;
+
+        let mut data = serde_json::to_vec(&self.secrets)?;
+        sealing_key(&self.key)?
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+            .map_err(|_| Error::Vault("Failed to encrypt vault".to_string()))?;
+
+        let file = VaultFile {
+            version: VERSION,
+            iterations: self.iterations,
+            salt: STANDARD.encode(self.salt),
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(data),
+        };
+
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        write_private(&self.path, serde_json::to_string_pretty(&file)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Write a file only the user can read (mode 0600 on unix).
+///
+/// The contents go to a temporary file next to it that is then renamed over
+/// it, so a failed write never leaves a truncated file behind.
+pub(crate) fn write_private(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+
+    let mut file = options.open(&tmp)?;
+    // The mode only applies to new files
+    #[cfg(unix)]
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> Result<[u8; KEY_LEN]> {
+    let iterations = NonZeroU32::new(iterations)
+        .ok_or_else(|| Error::Vault("Invalid vault iteration count".to_string()))?;
+
+    let mut key = [0u8; KEY_LEN];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    Ok(key)
+}
+
+fn sealing_key(key: &[u8; KEY_LEN]) -> Result<LessSafeKey> {
+    UnboundKey::new(&CHACHA20_POLY1305, key)
+        .map(LessSafeKey::new)
+        .map_err(|_| Error::Vault("Invalid vault key".to_string()))
+}
+
+fn fill_random(buf: &mut [u8]) -> Result<()> {
+    SystemRandom::new()
+        .fill(buf)
+        .map_err(|_| Error::Vault("No secure random source available".to_string()))
+}
+
+fn decode<const N: usize>(value: &str, field: &str) -> Result<[u8; N]> {
+    STANDARD
+        .decode(value)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| Error::Vault(format!("Invalid vault {}", field)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_ITERATIONS: u32 = 1_000;
+
+    #[test]
+    fn test_vault_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(VAULT_FILE);
+
+        let mut vault = Vault::create_with_iterations(&path, "hunter2", TEST_ITERATIONS).unwrap();
+        vault.set(TOKEN_KEY, "secret-token");
+        vault.save().unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("secret-token"));
+
+        let mut vault = Vault::unlock(&path, "hunter2").unwrap();
+        assert_eq!(vault.get(TOKEN_KEY), Some("secret-token"));
+        assert_eq!(vault.get(DEVICE_ID_KEY), None);
+
+        // Saving again re-encrypts with the same passphrase
+        vault.set(DEVICE_ID_KEY, "device");
+        vault.save().unwrap();
+        assert_ne!(fs::read_to_string(&path).unwrap(), content);
+        let vault = Vault::unlock(&path, "hunter2").unwrap();
+        assert_eq!(vault.get(DEVICE_ID_KEY), Some("device"));
+
+        #[cfg(unix)]
+        assert_eq!(
+            fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+    }
+
+    #[test]
+    fn test_vault_save_replaces_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(VAULT_FILE);
+        let tmp = dir.path().join(format!("{}.tmp", VAULT_FILE));
+
+        let mut vault = Vault::create_with_iterations(&path, "hunter2", TEST_ITERATIONS).unwrap();
+        vault.set(TOKEN_KEY, "secret-token");
+        vault.save().unwrap();
+
+        // A temporary file left by an interrupted save is overwritten
+        fs::write(&tmp, "partial").unwrap();
+        vault.set(DEVICE_ID_KEY, "device");
+        vault.save().unwrap();
+
+        assert!(!tmp.exists());
+        let vault = Vault::unlock(&path, "hunter2").unwrap();
+        assert_eq!(vault.get(TOKEN_KEY), Some("secret-token"));
+        assert_eq!(vault.get(DEVICE_ID_KEY), Some("device"));
+
+        #[cfg(unix)]
+        assert_eq!(
+            fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+    }
+
+    #[test]
+    fn test_vault_rejects_wrong_passphrase_and_tampering() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(VAULT_FILE);
+
+        let mut vault = Vault::create_with_iterations(&path, "hunter2", TEST_ITERATIONS).unwrap();
+        vault.set(TOKEN_KEY, "secret-token");
+        vault.save().unwrap();
+
+        let error = Vault::unlock(&path, "hunter3").unwrap_err();
+        assert!(error.to_string().contains("Wrong passphrase"));
+
+        let mut file: VaultFile =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let mut data = STANDARD.decode(&file.ciphertext).unwrap();
+        data[0] ^= 1;
+        file.ciphertext = STANDARD.encode(data);
+        fs::write(&path, serde_json::to_string(&file).unwrap()).unwrap();
+        assert!(Vault::unlock(&path, "hunter2").is_err());
+
+        assert!(Vault::create(&path, "").is_err());
+    }
+}
@@ -0,0 +1,507 @@
+//! Scheduling for the `daemon` command.
+//!
+//! Each creator and source (timeline, messages, collections, stories) is a job
+//! with its own interval. Intervals come from `[daemon.creators]`, then
+//! `[daemon.sources]`, then `[daemon] interval`, and every run is pushed back by
+//! a random jitter. Progress is reported in a JSON status file that doubles as
+//! a heartbeat.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+use rand::Rng;
+use serde::Serialize;
+
+use crate::config::{DaemonConfig, DownloadMode, DownloadType};
+use crate::error::{Error, Result};
+
+/// Longest accepted interval or jitter (one year).
+const MAX_DURATION: Duration = Duration::from_secs(365 * 86400);
+
+/// Parse a duration such as "90s", "15m", "2h", "1d" or "1h30m".
+///
+/// A bare number is taken as seconds.
+pub fn parse_duration(input: &str) -> std::result::Result<Duration, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("empty duration".to_string());
+    }
+    if let Ok(seconds) = input.parse::<u64>() {
+        return Ok(Duration::from_secs(seconds));
+    }
+
+    let mut total = 0u64;
+    let mut number = String::new();
+    for c in input.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let unit = match c.to_ascii_lowercase() {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86400,
+            c if c.is_whitespace() => continue,
+            _ => return Err(format!("invalid duration '{}'", input)),
+        };
+        let value: u64 = number
+            .parse()
+            .map_err(|_| format!("invalid duration '{}'", input))?;
+        total = value
+            .checked_mul(unit)
+            .and_then(|seconds| total.checked_add(seconds))
+            .ok_or_else(|| format!("duration '{}' is too long", input))?;
+        number.clear();
+    }
+
+    if !number.is_empty() {
+        return Err(format!("missing unit in duration '{}'", input));
+    }
+
+    Ok(Duration::from_secs(total))
+}
+
+/// Get the sources synced for a download mode.
+pub fn sources_for_mode(mode: DownloadMode) -> &'static [DownloadType] {
+    match mode {
+        DownloadMode::Normal => &[
+            DownloadType::Timeline,
+            DownloadType::Messages,
+            DownloadType::Collections,
+            DownloadType::Stories,
+        ],
+        DownloadMode::Timeline => &[DownloadType::Timeline],
+        DownloadMode::Messages => &[DownloadType::Messages],
+        DownloadMode::Collection => &[DownloadType::Collections],
+        DownloadMode::Stories => &[DownloadType::Stories],
+        DownloadMode::Single => &[],
+    }
+}
+
+/// Parse a source name as used in `[daemon.sources]`.
+fn parse_source(name: &str) -> Option<DownloadType> {
+    match name.to_lowercase().as_str() {
+        "timeline" => Some(DownloadType::Timeline),
+        "messages" => Some(DownloadType::Messages),
+        "collection" | "collections" => Some(DownloadType::Collections),
+        "stories" => Some(DownloadType::Stories),
+        _ => None,
+    }
+}
+
+/// Parse a duration from the daemon configuration, at most [`MAX_DURATION`].
+fn parse_field(field: &str, value: &str) -> Result<Duration> {
+    let duration = parse_duration(value).map_err(|message| Error::ConfigValidation {
+        field: field.to_string(),
+        message,
+    })?;
+    if duration > MAX_DURATION {
+        return Err(Error::ConfigValidation {
+            field: field.to_string(),
+            message: format!("'{}' is longer than a year", value),
+        });
+    }
+    Ok(duration)
+}
+
+/// A creator and source synced on its own interval.
+#[derive(Debug, Clone)]
+struct Job {
+    creator: String,
+    source: DownloadType,
+    interval: Duration,
+    next_run: Instant,
+}
+
+/// Run times of all creator and source jobs.
+#[derive(Debug)]
+pub struct Schedule {
+    jobs: Vec<Job>,
+    jitter: Duration,
+}
+
+impl Schedule {
+    /// Build the schedule for creators with their download modes, with every
+    /// job due at `now`.
+    pub fn from_config(
+        daemon: &DaemonConfig,
+        creators: &[(String, DownloadMode)],
+        now: Instant,
+    ) -> Result<Self> {
+        let interval = parse_field("daemon.interval", &daemon.interval)?;
+        if interval.is_zero() {
+            return Err(Error::ConfigValidation {
+                field: "daemon.interval".to_string(),
+                message: "Must be greater than zero".to_string(),
+            });
+        }
+        let jitter = parse_field("daemon.jitter", &daemon.jitter)?;
+
+        let mut source_intervals = BTreeMap::new();
+        for (name, value) in &daemon.sources {
+            let source = parse_source(name).ok_or_else(|| Error::ConfigValidation {
+                field: format!("daemon.sources.{}", name),
+                message: "Unknown source (expected timeline, messages, collections or stories)"
+                    .to_string(),
+            })?;
+            let field = format!("daemon.sources.{}", name);
+            source_intervals.insert(source.to_string(), parse_field(&field, value)?);
+        }
+
+        let mut creator_intervals = BTreeMap::new();
+        for (name, value) in &daemon.creators {
+            let field = format!("daemon.creators.{}", name);
+            creator_intervals.insert(normalize(name), parse_field(&field, value)?);
+        }
+
+        let mut jobs = Vec::new();
+        for (creator, mode) in creators {
+            let sources = sources_for_mode(*mode);
+            if sources.is_empty() {
+                return Err(Error::ConfigValidation {
+                    field: "download_mode".to_string(),
+                    message: format!("'{}' can't be scheduled", mode),
+                });
+            }
+
+            for &source in sources {
+                let interval = creator_intervals
+                    .get(&normalize(creator))
+                    .or_else(|| source_intervals.get(&source.to_string()))
+                    .copied()
+                    .unwrap_or(interval);
+
+                jobs.push(Job {
+                    creator: creator.clone(),
+                    source,
+                    interval,
+                    next_run: now,
+                });
+            }
+        }
+
+        Ok(Self { jobs, jitter })
+    }
+
+    /// Get the time of the next due job.
+    pub fn next_run(&self) -> Option<Instant> {
+        self.jobs.iter().map(|job| job.next_run).min()
+    }
+
+    /// Get the jobs due at `now`, grouped by creator.
+    pub fn due(&self, now: Instant) -> Vec<(String, Vec<DownloadType>)> {
+        let mut due: Vec<(String, Vec<DownloadType>)> = Vec::new();
+
+        for job in self.jobs.iter().filter(|job| job.next_run <= now) {
+            match due.iter_mut().find(|(creator, _)| *creator == job.creator) {
+                Some((_, sources)) => sources.push(job.source),
+                None => due.push((job.creator.clone(), vec![job.source])),
+            }
+        }
+
+        due
+    }
+
+    /// Schedule the next run of a job after it ran at `now`, returning its time.
+    pub fn reschedule(&mut self, creator: &str, source: DownloadType, now: Instant) -> Instant {
+        let jitter = self.random_jitter();
+        let mut next = now;
+
+        if let Some(job) = self
+            .jobs
+            .iter_mut()
+            .find(|job| job.creator == creator && job.source == source)
+        {
+            job.next_run = now + job.interval + jitter;
+            next = job.next_run;
+        }
+
+        next
+    }
+
+    /// Get the interval of a job.
+    pub fn interval(&self, creator: &str, source: DownloadType) -> Option<Duration> {
+        self.jobs
+            .iter()
+            .find(|job| job.creator == creator && job.source == source)
+            .map(|job| job.interval)
+    }
+
+    fn random_jitter(&self) -> Duration {
+        if self.jitter.is_zero() {
+            return Duration::ZERO;
+        }
+        let millis = rand::thread_rng().gen_range(0..=self.jitter.as_millis() as u64);
+        Duration::from_millis(millis)
+    }
+}
+
+/// Normalize a creator name for matching config keys.
+fn normalize(name: &str) -> String {
+    name.trim_start_matches('@').to_lowercase()
+}
+
+/// Convert a monotonic time into wall clock time.
+pub fn wall_clock(at: Instant) -> DateTime<Local> {
+    let remaining = at.saturating_duration_since(Instant::now());
+    Local::now() + chrono::Duration::from_std(remaining).unwrap_or_default()
+}
+
+/// What the daemon is doing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DaemonState {
+    Idle,
+    Running,
+    Stopped,
+}
+
+/// Last outcome of a creator and source job.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct JobStatus {
+    pub last_run: Option<DateTime<Local>>,
+    pub last_success: Option<DateTime<Local>>,
+    pub last_error: Option<String>,
+    pub last_downloaded: u64,
+    pub next_run: Option<DateTime<Local>>,
+}
+
+/// Contents of the daemon status file.
+#[derive(Debug, Clone, Serialize)]
+pub struct DaemonStatus {
+    pub pid: u32,
+    pub state: DaemonState,
+    pub started_at: DateTime<Local>,
+    /// Updated on every write; a stale heartbeat means the daemon is stuck or gone.
+    pub heartbeat: DateTime<Local>,
+    pub cycles: u64,
+    pub next_run: Option<DateTime<Local>>,
+    /// Job status by creator, then source.
+    pub jobs: BTreeMap<String, BTreeMap<String, JobStatus>>,
+}
+
+impl Default for DaemonStatus {
+    fn default() -> Self {
+        let now = Local::now();
+        Self {
+            pid: std::process::id(),
+            state: DaemonState::Idle,
+            started_at: now,
+            heartbeat: now,
+            cycles: 0,
+            next_run: None,
+            jobs: BTreeMap::new(),
+        }
+    }
+}
+
+impl DaemonStatus {
+    /// Record the outcome of a job: the number of files downloaded, or an error.
+    pub fn record(
+        &mut self,
+        creator: &str,
+        source: DownloadType,
+        outcome: std::result::Result<u64, String>,
+        next_run: DateTime<Local>,
+    ) {
+        let now = Local::now();
+        let job = self
+            .jobs
+            .entry(creator.to_string())
+            .or_default()
+            .entry(source.to_string())
+            .or_default();
+
+        job.last_run = Some(now);
+        job.next_run = Some(next_run);
+        match outcome {
+            Ok(downloaded) => {
+                job.last_success = Some(now);
+                job.last_error = None;
+                job.last_downloaded = downloaded;
+            }
+            Err(e) => {
+                job.last_error = Some(e);
+                job.last_downloaded = 0;
+            }
+        }
+    }
+
+    /// Refresh the heartbeat and write the status file atomically.
+    pub fn write(&mut self, path: &Path) -> Result<()> {
+        self.heartbeat = Local::now();
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content = serde_json::to_string_pretty(self)?;
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, content)?;
+        fs::rename(&tmp, path)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("45s"), Ok(Duration::from_secs(45)));
+        assert_eq!(parse_duration("15m"), Ok(Duration::from_secs(900)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("1d 2h"), Ok(Duration::from_secs(93600)));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("1h30").is_err());
+        assert!(parse_duration("10x").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("300000000000000d").is_err());
+        assert!(parse_duration("18446744073709551615s 1s").is_err());
+    }
+
+    fn make_daemon_config() -> DaemonConfig {
+        let mut daemon = DaemonConfig {
+            interval: "1h".to_string(),
+            jitter: "0".to_string(),
+            ..Default::default()
+        };
+        daemon.sources.insert("stories".into(), "15m".into());
+        daemon.creators.insert("@Alice".into(), "10m".into());
+        daemon
+    }
+
+    #[test]
+    fn test_interval_precedence() {
+        let creators = vec![
+            ("alice".to_string(), DownloadMode::Normal),
+            ("bob".to_string(), DownloadMode::Normal),
+            ("carol".to_string(), DownloadMode::Timeline),
+        ];
+        let schedule =
+            Schedule::from_config(&make_daemon_config(), &creators, Instant::now()).unwrap();
+
+        let minutes = |m: u64| Some(Duration::from_secs(m * 60));
+        assert_eq!(
+            schedule.interval("alice", DownloadType::Stories),
+            minutes(10)
+        );
+        assert_eq!(schedule.interval("bob", DownloadType::Stories), minutes(15));
+        assert_eq!(
+            schedule.interval("bob", DownloadType::Timeline),
+            minutes(60)
+        );
+
+        // Only the sources of the creator's download mode are scheduled
+        assert_eq!(
+            schedule.interval("carol", DownloadType::Timeline),
+            minutes(60)
+        );
+        assert_eq!(schedule.interval("carol", DownloadType::Stories), None);
+    }
+
+    #[test]
+    fn test_due_and_reschedule() {
+        let start = Instant::now();
+        let creators = vec![
+            ("alice".to_string(), DownloadMode::Normal),
+            ("bob".to_string(), DownloadMode::Normal),
+        ];
+        let mut schedule = Schedule::from_config(&make_daemon_config(), &creators, start).unwrap();
+
+        // Everything is due at start, grouped by creator
+        let due = schedule.due(start);
+        assert_eq!(due.len(), 2);
+        assert_eq!(due[1].1.len(), 4);
+
+        for (creator, sources) in &due {
+            for &source in sources {
+                schedule.reschedule(creator, source, start);
+            }
+        }
+        assert!(schedule.due(start).is_empty());
+        assert_eq!(schedule.next_run(), Some(start + Duration::from_secs(600)));
+
+        let later = start + Duration::from_secs(16 * 60);
+        let due = schedule.due(later);
+        assert_eq!(
+            due,
+            vec![
+                (
+                    "alice".to_string(),
+                    sources_for_mode(DownloadMode::Normal).to_vec()
+                ),
+                ("bob".to_string(), vec![DownloadType::Stories]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_jitter_bounds() {
+        let start = Instant::now();
+        let daemon = DaemonConfig {
+            interval: "1m".to_string(),
+            jitter: "30s".to_string(),
+            ..Default::default()
+        };
+        let creators = vec![("alice".to_string(), DownloadMode::Timeline)];
+        let mut schedule = Schedule::from_config(&daemon, &creators, start).unwrap();
+
+        for _ in 0..20 {
+            let next = schedule.reschedule("alice", DownloadType::Timeline, start);
+            assert!(next >= start + Duration::from_secs(60));
+            assert!(next <= start + Duration::from_secs(90));
+        }
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let creators = vec![("alice".to_string(), DownloadMode::Normal)];
+        let mut daemon = DaemonConfig::default();
+        daemon.sources.insert("walls".into(), "1h".into());
+        assert!(Schedule::from_config(&daemon, &creators, Instant::now()).is_err());
+
+        let daemon = DaemonConfig::default();
+        let creators = vec![("alice".to_string(), DownloadMode::Single)];
+        assert!(Schedule::from_config(&daemon, &creators, Instant::now()).is_err());
+
+        // Parses, but would overflow when scheduled
+        let daemon = DaemonConfig {
+            interval: "200000000000000d".to_string(),
+            ..Default::default()
+        };
+        let creators = vec![("alice".to_string(), DownloadMode::Timeline)];
+        let error = Schedule::from_config(&daemon, &creators, Instant::now()).unwrap_err();
+        assert!(matches!(error, Error::ConfigValidation { .. }));
+    }
+
+    #[test]
+    fn test_status_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("status.json");
+
+        let mut status = DaemonStatus::default();
+        status.record("alice", DownloadType::Stories, Ok(3), Local::now());
+        status.record(
+            "alice",
+            DownloadType::Timeline,
+            Err("boom".into()),
+            Local::now(),
+        );
+        status.write(&path).unwrap();
+
+        let written: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written["state"], "idle");
+        assert_eq!(written["jobs"]["alice"]["stories"]["last_downloaded"], 3);
+        assert_eq!(written["jobs"]["alice"]["timeline"]["last_error"], "boom");
+    }
+}
@@ -0,0 +1,422 @@
+//! Diagnostics of the configuration and environment (`doctor` command).
+//!
+//! Unlike validation, which stops at the first error, every check runs and
+//! reports pass, warn or fail with a hint on how to fix it.
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use indicatif::HumanBytes;
+
+use crate::api::auth::is_device_id_expired;
+use crate::api::websocket::get_session_id;
+use crate::api::ProxyConfig;
+use crate::config::validation::{
+    validate_check_key, validate_options, validate_token, validate_user_agent, validate_usernames,
+};
+use crate::config::{Config, DownloadMode};
+use crate::error::Error;
+
+/// Free space below which downloads are likely to fail.
+const MIN_FREE_SPACE: u64 = 1024 * 1024 * 1024;
+
+/// Free space below which a warning is shown.
+const LOW_FREE_SPACE: u64 = 10 * 1024 * 1024 * 1024;
+
+/// Outcome of a check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// Result of a single check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub message: String,
+    /// How to fix the problem, for warnings and failures.
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Pass,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: &'static str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Warn,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(name: &'static str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Fail,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    /// Pass with a message, or fail with the validation error.
+    fn from_result(
+        name: &'static str,
+        result: crate::error::Result<()>,
+        message: impl Into<String>,
+        hint: &str,
+    ) -> Self {
+        match result {
+            Ok(()) => Self::pass(name, message),
+            Err(e) => Self::fail(name, error_message(&e), hint),
+        }
+    }
+}
+
+/// Check the credentials, creators, options and cached device ID.
+pub fn check_config(config: &Config) -> Vec<Check> {
+    let account = &config.my_account;
+    let mut checks = vec![check_token(&account.authorization_token)];
+
+    checks.push(match validate_user_agent(&account.user_agent) {
+        Err(e) => Check::fail(
+            "User agent",
+            error_message(&e),
+            "Copy the User-Agent header of your browser",
+        ),
+        Ok(()) if !account.user_agent.starts_with("Mozilla/5.0") => Check::warn(
+            "User agent",
+            "Doesn't look like a browser user agent",
+            "Use the User-Agent header of the browser you are logged in with",
+        ),
+        Ok(()) => Check::pass("User agent", "Looks like a browser user agent"),
+    });
+
+    checks.push(Check::from_result(
+        "Check key",
+        validate_check_key(&account.check_key),
+        "Set",
+        "Remove check_key from the config to use the default",
+    ));
+
+    let usernames = config.targeted_creator.usernames();
+    checks.push(
+        if usernames.is_empty() && config.options.download_mode == DownloadMode::Single {
+            Check::pass("Creators", "Taken from the downloaded posts")
+        } else if usernames.is_empty() {
+            Check::warn(
+                "Creators",
+                "No creators configured",
+                "Add usernames to [creators] or pass --user",
+            )
+        } else {
+            Check::from_result(
+                "Creators",
+                validate_usernames(&usernames),
+                format!("{} configured", usernames.len()),
+                "Use the username from the creator's profile URL",
+            )
+        },
+    );
+
+    checks.push(Check::from_result(
+        "Options",
+        validate_options(config),
+        "Valid",
+        "Fix the value in the config file or on the command line",
+    ));
+
+    checks.push(check_device_id(config));
+    checks
+}
+
+/// Check the download directory, free disk space and ffmpeg.
+pub fn check_environment(config: &Config) -> Vec<Check> {
+    let download_dir = config.download_directory();
+    vec![
+        check_writable(&download_dir),
+        check_free_space(&download_dir),
+        check_ffmpeg(),
+    ]
+}
+
+/// Check that the WebSocket and API are reachable and accept the token.
+pub async fn check_connectivity(config: &Config) -> Vec<Check> {
+    let account = &config.my_account;
+    let proxy = match ProxyConfig::resolve(config.options.proxy.as_deref()) {
+        Ok(proxy) => proxy,
+        Err(e) => {
+            return vec![Check::fail(
+                "Proxy",
+                error_message(&e),
+                "Use an http://, https://, socks5:// or socks5h:// proxy URL",
+            )]
+        }
+    };
+
+    let websocket = get_session_id(
+        &account.authorization_token,
+        &account.user_agent,
+        proxy.as_ref(),
+    )
+    .await;
+    if let Err(e) = websocket {
+        return vec![
+            Check::fail(
+                "WebSocket",
+                error_message(&e),
+                "Check your connection and proxy, and that the token is current",
+            ),
+            Check::warn("API", "Not checked", "Fix the WebSocket connection first"),
+        ];
+    }
+
+    let api = match config.build_api().await {
+        Ok(api) => api
+            .get_client_account_info()
+            .await
+            .map(|info| info.username),
+        Err(e) => Err(e),
+    };
+    vec![
+        Check::pass("WebSocket", "Session opened"),
+        match api {
+            Ok(username) => Check::pass("API", format!("Logged in as {}", username)),
+            Err(e) => Check::fail(
+                "API",
+                error_message(&e),
+                "The token may have expired; log in again and import it (import-auth)",
+            ),
+        },
+    ]
+}
+
+fn check_token(token: &str) -> Check {
+    const HINT: &str = "Copy the authorization header of an apiv3.fansly.com request";
+
+    if let Err(e) = validate_token(token) {
+        return Check::fail("Token", error_message(&e), HINT);
+    }
+    if token
+        .chars()
+        .any(|c| c.is_whitespace() || c == '"' || c == '\'')
+    {
+        return Check::fail("Token", "Contains whitespace or quotes", HINT);
+    }
+    Check::pass("Token", "Set")
+}
+
+fn check_device_id(config: &Config) -> Check {
+    let Some(device_id) = &config.cache.device_id else {
+        return Check::pass("Device ID", "None cached, fetched when connecting");
+    };
+
+    if device_id.trim().is_empty() {
+        Check::fail(
+            "Device ID",
+            "Empty",
+            "Remove device_id from [cache] to fetch one automatically",
+        )
+    } else if is_device_id_expired(config.cache.device_id_timestamp) {
+        Check::warn(
+            "Device ID",
+            "Older than 180 minutes",
+            "A new one is fetched when connecting",
+        )
+    } else {
+        Check::pass("Device ID", "Current")
+    }
+}
+
+fn check_writable(dir: &Path) -> Check {
+    const NAME: &str = "Download directory";
+
+    if !dir.exists() {
+        return match existing_ancestor(dir) {
+            Some(parent) if is_writable(parent) => {
+                Check::pass(NAME, format!("{} will be created", dir.display()))
+            }
+            _ => Check::fail(
+                NAME,
+                format!("{} can't be created", dir.display()),
+                "Set download_directory to a writable location",
+            ),
+        };
+    }
+
+    if !dir.is_dir() {
+        Check::fail(
+            NAME,
+            format!("{} is not a directory", dir.display()),
+            "Set download_directory to a directory",
+        )
+    } else if is_writable(dir) {
+        Check::pass(NAME, format!("{} is writable", dir.display()))
+    } else {
+        Check::fail(
+            NAME,
+            format!("{} is not writable", dir.display()),
+            "Fix the directory permissions or choose another download_directory",
+        )
+    }
+}
+
+fn check_free_space(dir: &Path) -> Check {
+    const NAME: &str = "Disk space";
+
+    let Some(free) = existing_ancestor(dir).and_then(free_space) else {
+        return Check::warn(
+            NAME,
+            "Unknown",
+            "Make sure the download disk has space left",
+        );
+    };
+
+    let message = format!("{} free", HumanBytes(free));
+    if free < MIN_FREE_SPACE {
+        Check::fail(
+            NAME,
+            message,
+            "Free up space or choose another download_directory",
+        )
+    } else if free < LOW_FREE_SPACE {
+        Check::warn(NAME, message, "Videos may not fit; free up space")
+    } else {
+        Check::pass(NAME, message)
+    }
+}
+
+fn check_ffmpeg() -> Check {
+    let found = Command::new("ffmpeg")
+        .arg("-version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+
+    if found {
+        Check::pass("ffmpeg", "Found")
+    } else {
+        Check::warn(
+            "ffmpeg",
+            "Not found in PATH",
+            "Install ffmpeg; streamed (HLS) videos can't be downloaded without it",
+        )
+    }
+}
+
+/// Get the closest directory that exists, starting with the path itself.
+fn existing_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors()
+        .find(|dir| !dir.as_os_str().is_empty() && dir.is_dir())
+        .or_else(|| path.is_relative().then_some(Path::new(".")))
+}
+
+fn is_writable(dir: &Path) -> bool {
+    let probe = dir.join(format!(".fansly-doctor-{}", std::process::id()));
+    let writable = fs::write(&probe, b"").is_ok();
+    let _ = fs::remove_file(&probe);
+    writable
+}
+
+/// Get the space available to the user on the disk of a directory.
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)]
+fn free_space(dir: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = CString::new(dir.as_os_str().as_bytes()).ok()?;
+    // SAFETY: `path` is a valid C string and `stat` is only read on success
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    Some((stat.f_bavail as u64).saturating_mul(stat.f_frsize as u64))
+}
+
+#[cfg(not(unix))]
+fn free_space(_dir: &Path) -> Option<u64> {
+    None
+}
+
+fn error_message(error: &Error) -> String {
+    match error {
+        Error::ConfigValidation { message, .. } => message.clone(),
+        Error::MissingConfig(_) => "Not set".to_string(),
+        error => error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Config {
+        toml::from_str(&format!(
+            "[my_account]\nauthorization_token = \"{}\"\n\n[creators]\nusernames = [\"creator1\"]\n",
+            "a".repeat(60)
+        ))
+        .unwrap()
+    }
+
+    fn status_of(checks: &[Check], name: &str) -> Status {
+        checks
+            .iter()
+            .find(|check| check.name == name)
+            .unwrap()
+            .status
+    }
+
+    #[test]
+    fn test_check_config_collects_every_problem() {
+        let checks = check_config(&config());
+        assert!(checks.iter().all(|check| check.status == Status::Pass));
+
+        let mut config = config();
+        config.my_account.authorization_token = "short".to_string();
+        config.my_account.user_agent = "curl/8.0".to_string();
+        config.targeted_creator.set_usernames(vec!["x".to_string()]);
+        config.options.max_concurrent_creators = 0;
+        config.cache.device_id = Some("device".to_string());
+        config.cache.device_id_timestamp = Some(0);
+
+        let checks = check_config(&config);
+        assert_eq!(status_of(&checks, "Token"), Status::Fail);
+        assert_eq!(status_of(&checks, "User agent"), Status::Fail);
+        assert_eq!(status_of(&checks, "Creators"), Status::Fail);
+        assert_eq!(status_of(&checks, "Options"), Status::Fail);
+        assert_eq!(status_of(&checks, "Device ID"), Status::Warn);
+        assert_eq!(status_of(&checks, "Check key"), Status::Pass);
+        assert!(checks
+            .iter()
+            .filter(|check| check.status != Status::Pass)
+            .all(|check| check.hint.is_some()));
+    }
+
+    #[test]
+    fn test_check_writable() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(check_writable(dir.path()).status, Status::Pass);
+
+        let missing = dir.path().join("a").join("b");
+        let check = check_writable(&missing);
+        assert_eq!(check.status, Status::Pass);
+        assert!(check.message.contains("will be created"));
+
+        let file = dir.path().join("file");
+        fs::write(&file, b"").unwrap();
+        assert_eq!(check_writable(&file).status, Status::Fail);
+    }
+}
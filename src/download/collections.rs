@@ -1,17 +1,11 @@
 //! Collections (purchased media) download logic.
 
-use std::time::Duration;
-
-use rand::Rng;
-use tokio::time::sleep;
-
 use crate::api::{FanslyApi, BATCH_SIZE};
 use crate::config::Config;
-use crate::download::media::download_media_item;
+use crate::download::media::{download_media_item, select_media_item};
 use crate::download::state::DownloadState;
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::fs::paths::get_download_path;
-use crate::media::parse_media_info;
 
 /// Download purchased media collections.
 pub async fn download_collections(
@@ -39,23 +33,18 @@ pub async fn download_collections(
 
     // Fetch and download media in batches
     for chunk in media_ids.chunks(BATCH_SIZE) {
-        // Rate limiting delay between batches
-        let delay_ms = rand::thread_rng().gen_range(400..750);
-        sleep(Duration::from_millis(delay_ms)).await;
-
         let media_infos = api.get_media_info(chunk).await?;
 
         for media_info in &media_infos {
-            if let Some(item) = parse_media_info(media_info, config.options.download_media_previews)
-            {
+            if let Some(item) = select_media_item(config, media_info) {
                 let target_dir = get_download_path(config, state, &item)?;
 
-                // Rate limiting delay between downloads
-                let delay_ms = rand::thread_rng().gen_range(400..750);
-                sleep(Duration::from_millis(delay_ms)).await;
-
-                if let Err(e) = download_media_item(api, config, state, &item, &target_dir).await {
-                    tracing::warn!("Failed to download media {}: {}", item.media_id, e);
+                match download_media_item(api, config, state, &item, &target_dir).await {
+                    Ok(_) => {}
+                    Err(Error::Interrupted) => return Err(Error::Interrupted),
+                    Err(e) => {
+                        tracing::warn!("Failed to download media {}: {}", item.media_id, e);
+                    }
                 }
             }
         }
@@ -0,0 +1,188 @@
+//! Persistent list of failed media downloads, retried by `retry-failed`.
+//!
+//! Entries are keyed by media ID. Retrying looks the media up again, so fresh
+//! download URLs are used.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::download::state::DownloadState;
+use crate::error::{Error, Result};
+use crate::media::MediaItem;
+
+/// Failed downloads filename inside the state directory.
+pub const FAILURES_FILE: &str = "failed.json";
+
+/// A media download that failed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailedDownload {
+    pub media_id: String,
+    pub creator_id: String,
+    pub creator_name: String,
+    /// Directory the file was being downloaded into.
+    pub target_dir: PathBuf,
+    pub is_preview: bool,
+    pub error: String,
+    pub failed_at: String,
+    #[serde(default = "default_attempts")]
+    pub attempts: u32,
+}
+
+impl FailedDownload {
+    /// Describe a failed download of `item` for the creator in `state`.
+    pub fn new(state: &DownloadState, item: &MediaItem, target_dir: &Path, error: &Error) -> Self {
+        Self {
+            media_id: item.media_id.clone(),
+            creator_id: state.creator_id.clone().unwrap_or_default(),
+            creator_name: state.creator_name.clone().unwrap_or_default(),
+            target_dir: target_dir.to_path_buf(),
+            is_preview: item.is_preview,
+            error: error.to_string(),
+            failed_at: Utc::now().to_rfc3339(),
+            attempts: 1,
+        }
+    }
+}
+
+/// Failed downloads waiting to be retried.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct FailedDownloads {
+    #[serde(default)]
+    failures: BTreeMap<String, FailedDownload>,
+
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl FailedDownloads {
+    /// Load the list from a file, or start an empty one if it doesn't exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut failures = if path.exists() {
+            let content = fs::read_to_string(path)?;
+            serde_json::from_str(&content)?
+        } else {
+            Self::default()
+        };
+        failures.path = Some(path.to_path_buf());
+        Ok(failures)
+    }
+
+    /// Load the list from the configured state directory.
+    pub fn load_for(config: &Config) -> Result<Self> {
+        Self::load(&config.state_directory().join(FAILURES_FILE))
+    }
+
+    /// Save the list to the file it was loaded from.
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Record a failure, counting repeated failures of the same media.
+    pub fn record(&mut self, failure: FailedDownload) {
+        let attempts = self
+            .failures
+            .get(&failure.media_id)
+            .map_or(0, |existing| existing.attempts);
+
+        let media_id = failure.media_id.clone();
+        self.failures.insert(
+            media_id,
+            FailedDownload {
+                attempts: attempts + failure.attempts,
+                ..failure
+            },
+        );
+    }
+
+    /// Remove a media ID after it was downloaded.
+    pub fn remove(&mut self, media_id: &str) -> Option<FailedDownload> {
+        self.failures.remove(media_id)
+    }
+
+    /// Get the failures grouped by creator ID.
+    pub fn by_creator(&self) -> BTreeMap<String, Vec<FailedDownload>> {
+        let mut groups: BTreeMap<String, Vec<FailedDownload>> = BTreeMap::new();
+        for failure in self.failures.values() {
+            groups
+                .entry(failure.creator_id.clone())
+                .or_default()
+                .push(failure.clone());
+        }
+        groups
+    }
+
+    /// Get the number of failed downloads.
+    pub fn len(&self) -> usize {
+        self.failures.len()
+    }
+
+    /// Check whether there are no failed downloads.
+    pub fn is_empty(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+fn default_attempts() -> u32 {
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure(media_id: &str, creator_id: &str) -> FailedDownload {
+        FailedDownload {
+            media_id: media_id.to_string(),
+            creator_id: creator_id.to_string(),
+            creator_name: format!("name_{}", creator_id),
+            target_dir: PathBuf::from("/downloads/creator/Pictures"),
+            is_preview: false,
+            error: "Download failed: timeout".to_string(),
+            failed_at: Utc::now().to_rfc3339(),
+            attempts: 1,
+        }
+    }
+
+    #[test]
+    fn test_record_counts_attempts() {
+        let mut failures = FailedDownloads::default();
+        failures.record(failure("m1", "c1"));
+        failures.record(failure("m1", "c1"));
+        failures.record(failure("m2", "c2"));
+
+        assert_eq!(failures.len(), 2);
+        let groups = failures.by_creator();
+        assert_eq!(groups["c1"][0].attempts, 2);
+        assert_eq!(groups["c2"].len(), 1);
+
+        assert!(failures.remove("m1").is_some());
+        assert_eq!(failures.len(), 1);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join(FAILURES_FILE);
+
+        let mut failures = FailedDownloads::load(&path).unwrap();
+        assert!(failures.is_empty());
+        failures.record(failure("m1", "c1"));
+        failures.save().unwrap();
+
+        let loaded = FailedDownloads::load(&path).unwrap();
+        assert_eq!(loaded.by_creator(), failures.by_creator());
+    }
+}
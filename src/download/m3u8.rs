@@ -8,6 +8,7 @@ use tokio::fs::{self, File};
 use tokio::io::AsyncWriteExt;
 use tokio::process::Command;
 
+use crate::api::throttle::throttle;
 use crate::api::FanslyApi;
 use crate::error::{Error, Result};
 use crate::media::MediaItem;
@@ -146,13 +147,14 @@ async fn download_segments(
 /// Download a single segment.
 async fn download_segment(api: &FanslyApi, url: &str, output: &Path) -> Result<()> {
     let response = api.download_file(url).await?;
-    let bytes = response
-        .bytes()
-        .await
-        .map_err(|e| Error::M3U8(format!("Failed to download segment: {}", e)))?;
+    let stream = throttle(response.bytes_stream(), api.bandwidth());
+    futures::pin_mut!(stream);
 
     let mut file = File::create(output).await?;
-    file.write_all(&bytes).await?;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| Error::M3U8(format!("Failed to download segment: {}", e)))?;
+        file.write_all(&chunk).await?;
+    }
     file.flush().await?;
 
     Ok(())
@@ -3,20 +3,55 @@
 use std::path::{Path, PathBuf};
 
 use futures::StreamExt;
-use indicatif::{ProgressBar, ProgressStyle};
 use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
 
+use crate::api::throttle::throttle;
+use crate::api::types::AccountMedia;
 use crate::api::FanslyApi;
 use crate::config::Config;
+use crate::download::failures::FailedDownload;
 use crate::download::m3u8::download_m3u8;
+use crate::download::shutdown::is_shutdown_requested;
 use crate::download::state::DownloadState;
 use crate::error::{Error, Result};
-use crate::media::{MediaItem, MediaType};
+use crate::media::{parse_media_info_capped, MediaItem, MediaType};
+use crate::output::create_download_bar;
 
 /// Minimum file size to show progress bar (20 MB).
 const PROGRESS_THRESHOLD: u64 = 20 * 1024 * 1024;
 
+/// Select the item to download from account media, applying the preview,
+/// quality cap and date floor options.
+pub fn select_media_item(config: &Config, media: &AccountMedia) -> Option<MediaItem> {
+    let item = parse_media_info_capped(
+        media,
+        config.options.download_media_previews,
+        config.options.max_height,
+    )?;
+
+    if is_before_since(config, item.created_at) {
+        return None;
+    }
+
+    Some(item)
+}
+
+/// Check whether a creation time is before the configured `since` date floor.
+pub(crate) fn is_before_since(config: &Config, created_at: i64) -> bool {
+    let Ok(Some(since)) = config.options.since_timestamp() else {
+        return false;
+    };
+
+    // Timestamps are in seconds or milliseconds depending on the endpoint
+    let created_at = if created_at < 1_000_000_000_000 {
+        created_at
+    } else {
+        created_at / 1000
+    };
+    created_at < since
+}
+
 /// Download a media item to the specified directory.
 pub async fn download_media_item(
     api: &FanslyApi,
@@ -54,14 +89,33 @@ pub async fn download_media_item(
         return Ok(None);
     }
 
+    // Files already started are finished, but no new ones after a shutdown request
+    if is_shutdown_requested() {
+        return Err(Error::Interrupted);
+    }
+
     // Ensure target directory exists
     tokio::fs::create_dir_all(target_dir).await?;
 
     // Download the file
-    let downloaded_path = if item.is_m3u8() {
-        download_m3u8(api, item, &output_path).await?
+    let result = if item.is_m3u8() {
+        download_m3u8(api, item, &output_path).await
     } else {
-        download_direct(api, config, item, &output_path).await?
+        download_direct(api, config, item, &output_path).await
+    };
+
+    let downloaded_path = match result {
+        Ok(path) => path,
+        Err(e) => {
+            // A partial file would be skipped as existing on the next run
+            for path in [output_path.clone(), media_output_path(item, target_dir)] {
+                let _ = tokio::fs::remove_file(path).await;
+            }
+            state
+                .failures
+                .push(FailedDownload::new(state, item, target_dir, &e));
+            return Err(e);
+        }
     };
 
     // Mark as seen and update stats
@@ -88,6 +142,18 @@ pub async fn download_media_item(
     Ok(Some(downloaded_path))
 }
 
+/// Get the final on-disk path of a media item in a target directory.
+///
+/// M3U8 streams are converted on download and saved with an `.mp4` extension.
+pub fn media_output_path(item: &MediaItem, target_dir: &Path) -> PathBuf {
+    let path = target_dir.join(item.generate_filename());
+    if item.is_m3u8() {
+        path.with_extension("mp4")
+    } else {
+        path
+    }
+}
+
 /// Download a file directly (non-M3U8).
 async fn download_direct(
     api: &FanslyApi,
@@ -105,21 +171,15 @@ async fn download_direct(
 
     // Create progress bar if needed
     let progress = if show_progress {
-        let pb = ProgressBar::new(content_length.unwrap_or(0));
-        pb.set_style(
-            ProgressStyle::default_bar()
-                .template("{spinner:.green} [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
-                .unwrap()
-                .progress_chars("#>-"),
-        );
-        Some(pb)
+        Some(create_download_bar(content_length.unwrap_or(0)))
     } else {
         None
     };
 
     // Stream to file
     let mut file = File::create(output_path).await?;
-    let mut stream = response.bytes_stream();
+    let stream = throttle(response.bytes_stream(), api.bandwidth());
+    futures::pin_mut!(stream);
     let mut downloaded: u64 = 0;
 
     while let Some(chunk) = stream.next().await {
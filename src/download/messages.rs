@@ -1,21 +1,23 @@
 //! Messages download logic.
 
-use std::time::Duration;
-
-use rand::Rng;
-use tokio::time::sleep;
+use std::collections::HashMap;
+use std::path::PathBuf;
 
 use crate::api::{FanslyApi, BATCH_SIZE};
 use crate::config::Config;
-use crate::download::media::download_media_item;
+use crate::download::media::{download_media_item, media_output_path, select_media_item};
 use crate::download::state::DownloadState;
-use crate::error::Result;
-use crate::fs::paths::get_download_path;
-use crate::media::{extract_media_ids, parse_media_info};
+use crate::download::transcript::{attachment_media_ids, Transcript, TRANSCRIPT_JSON};
+use crate::error::{Error, Result};
+use crate::fs::paths::{get_download_path, get_download_type_folder};
+use crate::media::extract_media_ids;
 
 /// Default duplicate threshold percentage for messages.
 const DUPLICATE_THRESHOLD_PERCENT: f64 = 0.2;
 
+/// Pages searched for an announced message before giving up.
+const MAX_MESSAGE_PAGES: usize = 4;
+
 /// Download messages for a creator.
 pub async fn download_messages(
     api: &FanslyApi,
@@ -50,11 +52,20 @@ pub async fn download_messages(
     let mut cursor = "0".to_string();
     let mut total_items = 0u64;
 
-    loop {
-        // Rate limiting delay between pages
-        let delay_ms = rand::thread_rng().gen_range(2000..4000);
-        sleep(Duration::from_millis(delay_ms)).await;
+    // Transcript export state
+    let transcript_dir = get_download_type_folder(config, state)?;
+    let mut transcript = if config.options.export_transcripts {
+        Some(Transcript::new(
+            group,
+            &creator_id,
+            state.creator_name.as_deref().unwrap_or("unknown"),
+        ))
+    } else {
+        None
+    };
+    let mut media_paths: HashMap<String, PathBuf> = HashMap::new();
 
+    loop {
         // Fetch messages page
         let messages = api.get_messages(&group_id, &cursor).await?;
 
@@ -68,31 +79,15 @@ pub async fn download_messages(
 
         total_items += media_ids.len() as u64;
 
-        // Fetch and download media in batches
-        for chunk in media_ids.chunks(BATCH_SIZE) {
-            // Rate limiting delay between batches
-            let delay_ms = rand::thread_rng().gen_range(400..750);
-            sleep(Duration::from_millis(delay_ms)).await;
-
-            let media_infos = api.get_media_info(chunk).await?;
-
-            for media_info in &media_infos {
-                if let Some(item) =
-                    parse_media_info(media_info, config.options.download_media_previews)
-                {
-                    let target_dir = get_download_path(config, state, &item)?;
-
-                    // Rate limiting delay between downloads
-                    let delay_ms = rand::thread_rng().gen_range(400..750);
-                    sleep(Duration::from_millis(delay_ms)).await;
+        download_message_media(api, config, state, &media_ids, &mut media_paths).await?;
 
-                    if let Err(e) =
-                        download_media_item(api, config, state, &item, &target_dir).await
-                    {
-                        tracing::warn!("Failed to download media {}: {}", item.media_id, e);
-                    }
-                }
-            }
+        if let Some(transcript) = transcript.as_mut() {
+            transcript.add_messages(
+                &messages.messages,
+                &messages.account_media_bundles,
+                &media_paths,
+                &transcript_dir,
+            );
         }
 
         // Check duplicate threshold
@@ -119,6 +114,19 @@ pub async fn download_messages(
         }
     }
 
+    if let Some(mut transcript) = transcript {
+        // Keep messages from earlier exports that were not fetched this run
+        if let Some(previous) = Transcript::load(&transcript_dir.join(TRANSCRIPT_JSON))? {
+            transcript.merge(previous);
+        }
+        transcript.write(&transcript_dir)?;
+        tracing::info!(
+            "Exported transcript with {} messages to {}",
+            transcript.messages.len(),
+            transcript_dir.display()
+        );
+    }
+
     tracing::info!(
         "Messages download complete: {} pictures, {} videos",
         state.pic_count,
@@ -127,3 +135,137 @@ pub async fn download_messages(
 
     Ok(())
 }
+
+/// Download the media of a single message, e.g. one announced as new.
+///
+/// Only the newest pages of the group are fetched, until the message is found.
+pub async fn download_message(
+    api: &FanslyApi,
+    config: &Config,
+    state: &mut DownloadState,
+    group_id: &str,
+    message_id: &str,
+) -> Result<()> {
+    let mut cursor = "0".to_string();
+
+    for _ in 0..MAX_MESSAGE_PAGES {
+        let messages = api.get_messages(group_id, &cursor).await?;
+
+        if let Some(message) = messages.messages.iter().find(|m| m.id == message_id) {
+            let media_ids =
+                attachment_media_ids(&message.attachments, &messages.account_media_bundles);
+            return download_message_media(api, config, state, &media_ids, &mut HashMap::new())
+                .await;
+        }
+
+        match messages.messages.last() {
+            Some(last) => cursor = last.id.clone(),
+            None => break,
+        }
+    }
+
+    tracing::warn!("Message {} not found in group {}", message_id, group_id);
+    Ok(())
+}
+
+/// Fetch and download message media in batches, recording the file of each.
+async fn download_message_media(
+    api: &FanslyApi,
+    config: &Config,
+    state: &mut DownloadState,
+    media_ids: &[String],
+    media_paths: &mut HashMap<String, PathBuf>,
+) -> Result<()> {
+    for chunk in media_ids.chunks(BATCH_SIZE) {
+        let media_infos = api.get_media_info(chunk).await?;
+
+        for media_info in &media_infos {
+            if let Some(item) = select_media_item(config, media_info) {
+                let target_dir = get_download_path(config, state, &item)?;
+
+                match download_media_item(api, config, state, &item, &target_dir).await {
+                    Ok(Some(path)) => {
+                        media_paths.insert(item.media_id.clone(), path);
+                    }
+                    Ok(None) => {
+                        // Skipped as duplicate; link the existing file if present
+                        let path = media_output_path(&item, &target_dir);
+                        if path.exists() {
+                            media_paths.insert(item.media_id.clone(), path);
+                        }
+                    }
+                    Err(Error::Interrupted) => return Err(Error::Interrupted),
+                    Err(e) => {
+                        tracing::warn!("Failed to download media {}: {}", item.media_id, e);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::stub::spawn_server;
+
+    /// Two pages of group `g1`: m3 and m2 (a bundle of x and y), then m1 (media z).
+    fn messages_page(path: &str) -> (u16, String) {
+        let body = if path.starts_with("/api/v1/account/media?") {
+            "[]"
+        } else if path.contains("before=m2") {
+            r#"{"messages":[{"id":"m1","senderId":"c","createdAt":1,
+                "attachments":[{"contentId":"z","contentType":1}]}]}"#
+        } else if path.starts_with("/api/v1/message?groupId=g1") {
+            r#"{"messages":[
+                {"id":"m3","senderId":"c","createdAt":3},
+                {"id":"m2","senderId":"c","createdAt":2,
+                 "attachments":[{"contentId":"b1","contentType":2}]}],
+               "accountMediaBundles":[{"id":"b1","accountId":"c",
+                 "accountMediaIds":["x","y"],"createdAt":2}]}"#
+        } else {
+            return (404, "{}".to_string());
+        };
+        (200, format!(r#"{{"success":true,"response":{}}}"#, body))
+    }
+
+    #[tokio::test]
+    async fn test_download_message_fetches_only_the_announced_message() {
+        let (url, requests) = spawn_server(messages_page).await;
+        let api = FanslyApi::builder("token")
+            .session_id("session")
+            .device_id("device", None)
+            .base_url(url)
+            .build()
+            .await
+            .unwrap();
+        let config = Config::default();
+        let mut state = DownloadState::new("creator".to_string(), "c".to_string());
+
+        download_message(&api, &config, &mut state, "g1", "m2")
+            .await
+            .unwrap();
+        download_message(&api, &config, &mut state, "g1", "m1")
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        let paths: Vec<&str> = requests
+            .iter()
+            .map(|r| r.split_whitespace().nth(1).unwrap())
+            .map(|p| p.split("&ngsw").next().unwrap())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/api/v1/message?groupid=g1&limit=25",
+                "/api/v1/account/media?ids=x,y",
+                "/api/v1/message?groupid=g1&limit=25",
+                "/api/v1/message?groupid=g1&limit=25&before=m2",
+                "/api/v1/account/media?ids=z",
+            ]
+        );
+    }
+}
@@ -2,24 +2,39 @@
 //!
 //! This module provides:
 //! - Download state tracking
+//! - Failed download tracking
 //! - Timeline downloading
 //! - Messages downloading
 //! - Single post downloading
 //! - Collections downloading
+//! - Stories downloading
 //! - Media file downloading
+//! - Graceful shutdown
+//! - Chat transcript export
+//! - Creator profile backup
 //! - M3U8/HLS handling
 
 pub mod collections;
+pub mod failures;
 pub mod m3u8;
 pub mod media;
 pub mod messages;
+pub mod profile;
+pub mod shutdown;
 pub mod single;
 pub mod state;
+pub mod stories;
 pub mod timeline;
+pub mod transcript;
 
 pub use collections::download_collections;
-pub use media::download_media_item;
-pub use messages::download_messages;
-pub use single::download_single_post;
+pub use failures::{FailedDownload, FailedDownloads};
+pub use media::{download_media_item, media_output_path, select_media_item};
+pub use messages::{download_message, download_messages};
+pub use profile::backup_profile;
+pub use shutdown::{is_shutdown_requested, request_shutdown, shutdown_requested};
+pub use single::{download_posts, download_single_post, fetch_posts_by_creator, CreatorPosts};
 pub use state::{DownloadState, GlobalState};
+pub use stories::download_stories;
 pub use timeline::download_timeline;
+pub use transcript::Transcript;
@@ -0,0 +1,270 @@
+//! Creator profile backup (avatar, banner, bio and stats).
+//!
+//! Every run writes a `profile.json` snapshot into the creator folder. When
+//! the bio, display name, links, avatar or banner changed since the previous
+//! snapshot, the previous one is archived into `Profile/history/`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::api::types::{AccountInfo, MediaDetails};
+use crate::api::FanslyApi;
+use crate::config::Config;
+use crate::download::media::{download_media_item, media_output_path};
+use crate::download::state::DownloadState;
+use crate::error::{Error, Result};
+use crate::media::parse_media_details;
+
+/// Profile snapshot filename in the creator folder.
+pub const PROFILE_JSON: &str = "profile.json";
+
+/// Folder (inside the creator folder) for avatar, banner and history.
+const PROFILE_FOLDER: &str = "Profile";
+
+/// Folder (inside the profile folder) for archived snapshots.
+const HISTORY_FOLDER: &str = "history";
+
+/// Snapshot of a creator's public profile.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProfileSnapshot {
+    pub account_id: String,
+    pub username: String,
+    pub display_name: Option<String>,
+    pub about: Option<String>,
+    pub location: Option<String>,
+    #[serde(default)]
+    pub links: Vec<String>,
+    pub avatar_media_id: Option<String>,
+    pub avatar_file: Option<String>,
+    pub banner_media_id: Option<String>,
+    pub banner_file: Option<String>,
+    pub follow_count: Option<u64>,
+    pub subscriber_count: Option<u64>,
+    pub image_count: Option<u64>,
+    pub video_count: Option<u64>,
+    pub bundle_count: Option<u64>,
+    pub captured_at: String,
+}
+
+impl ProfileSnapshot {
+    /// Build a snapshot from account info.
+    pub fn from_account(account: &AccountInfo) -> Self {
+        let stats = account.timeline_stats.as_ref();
+
+        Self {
+            account_id: account.id.clone(),
+            username: account.username.clone(),
+            display_name: account.display_name.clone(),
+            about: account.about.clone(),
+            location: account.location.clone(),
+            links: account
+                .about
+                .as_deref()
+                .map(extract_links)
+                .unwrap_or_default(),
+            avatar_media_id: account.avatar.as_ref().map(|m| m.id.clone()),
+            avatar_file: None,
+            banner_media_id: account.banner.as_ref().map(|m| m.id.clone()),
+            banner_file: None,
+            follow_count: account.follow_count,
+            subscriber_count: account.subscriber_count,
+            image_count: stats.and_then(|s| s.image_count),
+            video_count: stats.and_then(|s| s.video_count),
+            bundle_count: stats.and_then(|s| s.bundle_count),
+            captured_at: Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// Check whether the profile content (not stats) differs from another snapshot.
+    pub fn content_changed(&self, other: &ProfileSnapshot) -> bool {
+        self.username != other.username
+            || self.display_name != other.display_name
+            || self.about != other.about
+            || self.location != other.location
+            || self.links != other.links
+            || self.avatar_media_id != other.avatar_media_id
+            || self.banner_media_id != other.banner_media_id
+    }
+}
+
+/// Back up a creator's profile: download avatar and banner, write `profile.json`,
+/// and archive the previous snapshot if the profile changed.
+pub async fn backup_profile(
+    api: &FanslyApi,
+    config: &Config,
+    state: &mut DownloadState,
+    account: &AccountInfo,
+) -> Result<()> {
+    let creator_folder = state
+        .base_path
+        .clone()
+        .ok_or_else(|| Error::Download("Creator folder not set".into()))?;
+    let profile_dir = creator_folder.join(PROFILE_FOLDER);
+
+    let mut snapshot = ProfileSnapshot::from_account(account);
+
+    if let Some(avatar) = account.avatar.as_ref() {
+        snapshot.avatar_file =
+            download_profile_media(api, config, state, avatar, &profile_dir, &creator_folder).await;
+    }
+
+    if let Some(banner) = account.banner.as_ref() {
+        snapshot.banner_file =
+            download_profile_media(api, config, state, banner, &profile_dir, &creator_folder).await;
+    }
+
+    write_snapshot(&creator_folder, &snapshot)?;
+
+    Ok(())
+}
+
+/// Download an avatar or banner, returning its path relative to the creator folder.
+async fn download_profile_media(
+    api: &FanslyApi,
+    config: &Config,
+    state: &mut DownloadState,
+    details: &MediaDetails,
+    profile_dir: &Path,
+    creator_folder: &Path,
+) -> Option<String> {
+    let item = parse_media_details(details, false)?;
+
+    let path = match download_media_item(api, config, state, &item, profile_dir).await {
+        Ok(Some(path)) => path,
+        Ok(None) => media_output_path(&item, profile_dir),
+        Err(e) => {
+            // Profile media isn't account media, retry-failed can't look it up;
+            // the next backup downloads it again anyway
+            state.failures.retain(|f| f.media_id != item.media_id);
+            tracing::warn!("Failed to download profile media {}: {}", item.media_id, e);
+            return None;
+        }
+    };
+
+    path.strip_prefix(creator_folder)
+        .ok()
+        .map(|p| p.to_string_lossy().replace('\\', "/"))
+}
+
+/// Write a snapshot, archiving the previous one if the profile content changed.
+///
+/// Returns the path of the archived snapshot, if any.
+pub fn write_snapshot(
+    creator_folder: &Path,
+    snapshot: &ProfileSnapshot,
+) -> Result<Option<PathBuf>> {
+    fs::create_dir_all(creator_folder)?;
+    let profile_path = creator_folder.join(PROFILE_JSON);
+    let mut archived = None;
+
+    if profile_path.exists() {
+        let content = fs::read_to_string(&profile_path)?;
+        match serde_json::from_str::<ProfileSnapshot>(&content) {
+            Ok(previous) if previous.content_changed(snapshot) => {
+                let history_dir = creator_folder.join(PROFILE_FOLDER).join(HISTORY_FOLDER);
+                fs::create_dir_all(&history_dir)?;
+
+                let archive_path = history_dir.join(format!(
+                    "profile_{}.json",
+                    previous.captured_at.replace(':', "-")
+                ));
+                fs::write(&archive_path, content)?;
+                tracing::info!("Profile of {} changed since last run", snapshot.username);
+                archived = Some(archive_path);
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!("Ignoring unreadable {}: {}", profile_path.display(), e);
+            }
+        }
+    }
+
+    fs::write(&profile_path, serde_json::to_string_pretty(snapshot)?)?;
+
+    Ok(archived)
+}
+
+/// Extract URLs from a profile bio.
+pub fn extract_links(about: &str) -> Vec<String> {
+    let url_pattern = Regex::new(r#"https?://[^\s<>"')\]]+"#).unwrap();
+
+    let mut links: Vec<String> = Vec::new();
+    for m in url_pattern.find_iter(about) {
+        let link = m
+            .as_str()
+            .trim_end_matches(['.', ',', '!', '?'])
+            .to_string();
+        if !links.contains(&link) {
+            links.push(link);
+        }
+    }
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_snapshot(about: &str, avatar: &str) -> ProfileSnapshot {
+        ProfileSnapshot {
+            account_id: "1".to_string(),
+            username: "creator".to_string(),
+            about: Some(about.to_string()),
+            avatar_media_id: Some(avatar.to_string()),
+            captured_at: "2024-01-01T00:00:00+00:00".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_extract_links() {
+        let about = "Hi! Find me at https://example.com/me, and (https://other.org).";
+        assert_eq!(
+            extract_links(about),
+            vec!["https://example.com/me", "https://other.org"]
+        );
+        assert!(extract_links("no links here").is_empty());
+    }
+
+    #[test]
+    fn test_content_changed_ignores_stats() {
+        let a = make_snapshot("bio", "avatar1");
+        let mut b = a.clone();
+        b.subscriber_count = Some(100);
+        b.captured_at = "2024-02-01T00:00:00+00:00".to_string();
+        assert!(!a.content_changed(&b));
+
+        b.avatar_media_id = Some("avatar2".to_string());
+        assert!(a.content_changed(&b));
+    }
+
+    #[test]
+    fn test_write_snapshot_archives_on_change() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let first = make_snapshot("old bio", "avatar1");
+        assert!(write_snapshot(dir.path(), &first).unwrap().is_none());
+
+        // Unchanged content does not create history
+        let mut same = first.clone();
+        same.captured_at = "2024-01-02T00:00:00+00:00".to_string();
+        assert!(write_snapshot(dir.path(), &same).unwrap().is_none());
+
+        let mut changed = make_snapshot("new bio", "avatar1");
+        changed.captured_at = "2024-01-03T00:00:00+00:00".to_string();
+        let archived = write_snapshot(dir.path(), &changed).unwrap().unwrap();
+
+        let archived: ProfileSnapshot =
+            serde_json::from_str(&fs::read_to_string(archived).unwrap()).unwrap();
+        assert_eq!(archived.about.as_deref(), Some("old bio"));
+
+        let current: ProfileSnapshot =
+            serde_json::from_str(&fs::read_to_string(dir.path().join(PROFILE_JSON)).unwrap())
+                .unwrap();
+        assert_eq!(current.about.as_deref(), Some("new bio"));
+    }
+}
@@ -0,0 +1,33 @@
+//! Graceful shutdown signalling.
+//!
+//! Once a shutdown is requested, downloads already in progress finish but no
+//! new files are started.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tokio::sync::Notify;
+
+static SHUTDOWN: AtomicBool = AtomicBool::new(false);
+static NOTIFY: Notify = Notify::const_new();
+
+/// Request a graceful shutdown.
+pub fn request_shutdown() {
+    SHUTDOWN.store(true, Ordering::SeqCst);
+    NOTIFY.notify_waiters();
+}
+
+/// Check whether a shutdown was requested.
+pub fn is_shutdown_requested() -> bool {
+    SHUTDOWN.load(Ordering::SeqCst)
+}
+
+/// Wait until a shutdown is requested.
+pub async fn shutdown_requested() {
+    loop {
+        let notified = NOTIFY.notified();
+        if is_shutdown_requested() {
+            return;
+        }
+        notified.await;
+    }
+}
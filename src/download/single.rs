@@ -1,17 +1,109 @@
 //! Single post download logic.
 
-use std::time::Duration;
-
-use rand::Rng;
-use tokio::time::sleep;
-
-use crate::api::FanslyApi;
+use crate::api::types::{AccountInfo, PostResponse};
+use crate::api::{FanslyApi, BATCH_SIZE};
 use crate::config::Config;
-use crate::download::media::download_media_item;
+use crate::download::media::{download_media_item, select_media_item};
 use crate::download::state::DownloadState;
 use crate::error::{Error, Result};
 use crate::fs::paths::get_download_path;
-use crate::media::{extract_media_ids, parse_media_info};
+use crate::media::extract_media_ids;
+
+/// Maximum post IDs per batch request.
+const POST_BATCH_SIZE: usize = 50;
+
+/// Posts owned by a single creator.
+#[derive(Debug)]
+pub struct CreatorPosts {
+    pub account: AccountInfo,
+    pub posts: PostResponse,
+}
+
+/// Fetch posts by ID in batches and group them by the creator that owns them.
+///
+/// Posts that could not be found are reported and skipped.
+pub async fn fetch_posts_by_creator(
+    api: &FanslyApi,
+    post_ids: &[String],
+) -> Result<Vec<CreatorPosts>> {
+    let mut combined = PostResponse::default();
+
+    for chunk in post_ids.chunks(POST_BATCH_SIZE) {
+        let response = api.get_posts(chunk).await?;
+        combined.posts.extend(response.posts);
+        combined.account_media.extend(response.account_media);
+        combined
+            .account_media_bundles
+            .extend(response.account_media_bundles);
+    }
+
+    for post_id in post_ids {
+        if !combined.posts.iter().any(|p| &p.id == post_id) {
+            tracing::warn!("Post not found: {}", post_id);
+        }
+    }
+
+    let groups = group_posts_by_account(combined);
+
+    // Resolve the owning creators
+    let account_ids: Vec<String> = groups.iter().map(|(id, _)| id.clone()).collect();
+    let mut accounts = Vec::new();
+    for chunk in account_ids.chunks(BATCH_SIZE) {
+        accounts.extend(api.get_accounts_by_ids(chunk).await?);
+    }
+
+    let mut result = Vec::new();
+    for (account_id, posts) in groups {
+        match accounts.iter().find(|a| a.id == account_id) {
+            Some(account) => result.push(CreatorPosts {
+                account: account.clone(),
+                posts,
+            }),
+            None => tracing::warn!(
+                "Creator {} of {} post(s) not found, skipping",
+                account_id,
+                posts.posts.len()
+            ),
+        }
+    }
+
+    Ok(result)
+}
+
+/// Split a post response by owning account, preserving first-seen order.
+fn group_posts_by_account(response: PostResponse) -> Vec<(String, PostResponse)> {
+    let mut groups: Vec<(String, PostResponse)> = Vec::new();
+
+    for post in response.posts {
+        match groups.iter_mut().find(|(id, _)| *id == post.account_id) {
+            Some((_, group)) => group.posts.push(post),
+            None => groups.push((
+                post.account_id.clone(),
+                PostResponse {
+                    posts: vec![post],
+                    ..Default::default()
+                },
+            )),
+        }
+    }
+
+    for (account_id, group) in &mut groups {
+        group.account_media = response
+            .account_media
+            .iter()
+            .filter(|m| &m.account_id == account_id)
+            .cloned()
+            .collect();
+        group.account_media_bundles = response
+            .account_media_bundles
+            .iter()
+            .filter(|b| &b.account_id == account_id)
+            .cloned()
+            .collect();
+    }
+
+    groups
+}
 
 /// Download a single post by ID.
 pub async fn download_single_post(
@@ -29,32 +121,45 @@ pub async fn download_single_post(
         return Err(Error::Api(format!("Post not found: {}", post_id)));
     }
 
+    download_posts(api, config, state, &post_response).await
+}
+
+/// Download the media of already fetched posts.
+pub async fn download_posts(
+    api: &FanslyApi,
+    config: &Config,
+    state: &mut DownloadState,
+    posts: &PostResponse,
+) -> Result<()> {
     // Extract media IDs
-    let media_ids = extract_media_ids(
-        &post_response.account_media,
-        &post_response.account_media_bundles,
-    );
+    let media_ids = extract_media_ids(&posts.account_media, &posts.account_media_bundles);
 
     if media_ids.is_empty() {
-        tracing::info!("No media found in post {}", post_id);
+        tracing::info!("No media found in {} post(s)", posts.posts.len());
         return Ok(());
     }
 
-    tracing::info!("Found {} media items in post", media_ids.len());
-
-    // Fetch and download media
-    let media_infos = api.get_media_info(&media_ids).await?;
+    tracing::info!(
+        "Found {} media items in {} post(s)",
+        media_ids.len(),
+        posts.posts.len()
+    );
 
-    for media_info in &media_infos {
-        if let Some(item) = parse_media_info(media_info, config.options.download_media_previews) {
-            let target_dir = get_download_path(config, state, &item)?;
+    // Fetch and download media in batches
+    for chunk in media_ids.chunks(BATCH_SIZE) {
+        let media_infos = api.get_media_info(chunk).await?;
 
-            // Rate limiting delay between downloads
-            let delay_ms = rand::thread_rng().gen_range(400..750);
-            sleep(Duration::from_millis(delay_ms)).await;
+        for media_info in &media_infos {
+            if let Some(item) = select_media_item(config, media_info) {
+                let target_dir = get_download_path(config, state, &item)?;
 
-            if let Err(e) = download_media_item(api, config, state, &item, &target_dir).await {
-                tracing::warn!("Failed to download media {}: {}", item.media_id, e);
+                match download_media_item(api, config, state, &item, &target_dir).await {
+                    Ok(_) => {}
+                    Err(Error::Interrupted) => return Err(Error::Interrupted),
+                    Err(e) => {
+                        tracing::warn!("Failed to download media {}: {}", item.media_id, e);
+                    }
+                }
             }
         }
     }
@@ -67,3 +172,63 @@ pub async fn download_single_post(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::types::{AccountMedia, MediaBundle, Post};
+
+    fn make_post(id: &str, account_id: &str) -> Post {
+        Post {
+            id: id.to_string(),
+            account_id: account_id.to_string(),
+            created_at: 0,
+            attachments: Vec::new(),
+        }
+    }
+
+    fn make_media(id: &str, account_id: &str) -> AccountMedia {
+        AccountMedia {
+            id: id.to_string(),
+            account_id: account_id.to_string(),
+            preview_id: None,
+            access: true,
+            media: None,
+            preview: None,
+        }
+    }
+
+    #[test]
+    fn test_group_posts_by_account() {
+        let response = PostResponse {
+            posts: vec![
+                make_post("p1", "a"),
+                make_post("p2", "b"),
+                make_post("p3", "a"),
+            ],
+            account_media: vec![make_media("m1", "a"), make_media("m2", "b")],
+            account_media_bundles: vec![MediaBundle {
+                id: "b1".to_string(),
+                account_id: "b".to_string(),
+                account_media_ids: vec!["m3".to_string()],
+                preview_id: None,
+                created_at: 0,
+            }],
+        };
+
+        let groups = group_posts_by_account(response);
+        assert_eq!(groups.len(), 2);
+
+        let (id, a) = &groups[0];
+        assert_eq!(id, "a");
+        assert_eq!(a.posts.len(), 2);
+        assert_eq!(a.account_media.len(), 1);
+        assert!(a.account_media_bundles.is_empty());
+
+        let (id, b) = &groups[1];
+        assert_eq!(id, "b");
+        assert_eq!(b.posts.len(), 1);
+        assert_eq!(b.account_media[0].id, "m2");
+        assert_eq!(b.account_media_bundles.len(), 1);
+    }
+}
@@ -4,6 +4,7 @@ use std::path::PathBuf;
 
 use crate::config::DownloadType;
 use crate::dedup::DedupService;
+use crate::download::failures::FailedDownload;
 use crate::media::MediaType;
 
 /// Per-creator download state.
@@ -22,9 +23,15 @@ pub struct DownloadState {
     // Current download type
     pub download_type: DownloadType,
 
+    // Current timeline wall folder name (when downloading by wall)
+    pub wall_name: Option<String>,
+
     // Unified deduplication service
     pub dedup: DedupService,
 
+    // Failed media downloads, saved for retry-failed
+    pub failures: Vec<FailedDownload>,
+
     // Statistics
     pub pic_count: u64,
     pub vid_count: u64,
@@ -0,0 +1,146 @@
+//! Stories download logic.
+//!
+//! Stories are time-limited, so only the currently active ones can be fetched.
+//! Running this mode regularly captures stories before they expire.
+
+use std::collections::HashSet;
+
+use crate::api::types::{Attachment, StoriesResponse};
+use crate::api::{FanslyApi, BATCH_SIZE};
+use crate::config::Config;
+use crate::download::media::{download_media_item, select_media_item};
+use crate::download::state::DownloadState;
+use crate::error::{Error, Result};
+use crate::fs::paths::get_download_path;
+
+/// Download the active stories of a creator.
+pub async fn download_stories(
+    api: &FanslyApi,
+    config: &Config,
+    state: &mut DownloadState,
+) -> Result<()> {
+    let creator_id = state.creator_id()?.to_string();
+
+    tracing::info!(
+        "Downloading stories for {}...",
+        state.creator_name.as_deref().unwrap_or("unknown")
+    );
+
+    let stories = api.get_stories(&creator_id).await?;
+    let media_ids = story_media_ids(&stories);
+
+    if media_ids.is_empty() {
+        tracing::info!("No active stories found");
+        return Ok(());
+    }
+
+    tracing::info!(
+        "Found {} active stories with {} media items",
+        stories.media_stories.len(),
+        media_ids.len()
+    );
+
+    // Fetch and download media in batches
+    for chunk in media_ids.chunks(BATCH_SIZE) {
+        let media_infos = api.get_media_info(chunk).await?;
+
+        for media_info in &media_infos {
+            if let Some(item) = select_media_item(config, media_info) {
+                let target_dir = get_download_path(config, state, &item)?;
+
+                match download_media_item(api, config, state, &item, &target_dir).await {
+                    Ok(_) => {}
+                    Err(Error::Interrupted) => return Err(Error::Interrupted),
+                    Err(e) => {
+                        tracing::warn!("Failed to download media {}: {}", item.media_id, e);
+                    }
+                }
+            }
+        }
+    }
+
+    tracing::info!(
+        "Stories download complete: {} pictures, {} videos",
+        state.pic_count,
+        state.vid_count
+    );
+
+    Ok(())
+}
+
+/// Collect the unique media IDs referenced by active stories.
+fn story_media_ids(stories: &StoriesResponse) -> Vec<String> {
+    let bundles = &stories.aggregation_data.account_media_bundles;
+    let mut ids: Vec<String> = Vec::new();
+
+    for story in &stories.media_stories {
+        match story.content_type {
+            Attachment::ACCOUNT_MEDIA => ids.push(story.content_id.clone()),
+            Attachment::MEDIA_BUNDLE => {
+                if let Some(bundle) = bundles.iter().find(|b| b.id == story.content_id) {
+                    ids.extend(bundle.account_media_ids.iter().cloned());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Remove duplicates while preserving order
+    let mut seen = HashSet::new();
+    ids.retain(|id| seen.insert(id.clone()));
+
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::types::{MediaBundle, MediaStory, StoriesAggregationData};
+
+    fn make_story(id: &str, content_type: i32, content_id: &str) -> MediaStory {
+        MediaStory {
+            id: id.to_string(),
+            account_id: "100".to_string(),
+            content_type,
+            content_id: content_id.to_string(),
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_story_media_ids() {
+        let stories = StoriesResponse {
+            media_stories: vec![
+                make_story("s1", Attachment::ACCOUNT_MEDIA, "m1"),
+                make_story("s2", Attachment::MEDIA_BUNDLE, "b1"),
+                make_story("s3", Attachment::ACCOUNT_MEDIA, "m1"),
+            ],
+            aggregation_data: StoriesAggregationData {
+                account_media: Vec::new(),
+                account_media_bundles: vec![MediaBundle {
+                    id: "b1".to_string(),
+                    account_id: "100".to_string(),
+                    account_media_ids: vec!["m2".to_string(), "m3".to_string()],
+                    preview_id: None,
+                    created_at: 0,
+                }],
+            },
+        };
+
+        assert_eq!(story_media_ids(&stories), vec!["m1", "m2", "m3"]);
+    }
+
+    #[test]
+    fn test_parse_stories_response() {
+        let json = r#"{
+            "mediaStories": [
+                {"id": "s1", "accountId": "100", "contentType": 1, "contentId": "m1", "createdAt": 1706011200}
+            ],
+            "aggregationData": {"accountMedia": []}
+        }"#;
+
+        let stories: StoriesResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(stories.media_stories.len(), 1);
+        assert_eq!(story_media_ids(&stories), vec!["m1"]);
+    }
+}
@@ -2,43 +2,107 @@
 
 use std::time::Duration;
 
-use rand::Rng;
 use tokio::time::sleep;
 
 use crate::api::{FanslyApi, BATCH_SIZE};
 use crate::config::Config;
-use crate::download::media::download_media_item;
+use crate::download::media::{download_media_item, is_before_since, select_media_item};
 use crate::download::state::DownloadState;
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::fs::paths::get_download_path;
-use crate::media::{extract_media_ids, parse_media_info};
+use crate::media::extract_media_ids;
 
 /// Default duplicate threshold percentage.
 const DUPLICATE_THRESHOLD_PERCENT: f64 = 0.2;
 
+/// Wall selector value that downloads every wall.
+const ALL_WALLS: &str = "all";
+
 /// Download timeline posts for a creator.
+///
+/// If a wall is configured, only that wall is downloaded (or every wall for
+/// "all"), each into its own folder.
 pub async fn download_timeline(
     api: &FanslyApi,
     config: &Config,
     state: &mut DownloadState,
 ) -> Result<()> {
     let creator_id = state.creator_id()?.to_string();
-    let mut cursor = "0".to_string();
-    let mut empty_response_count = 0;
-    let mut total_items = 0u64;
 
     tracing::info!(
         "Downloading timeline for {}...",
         state.creator_name.as_deref().unwrap_or("unknown")
     );
 
-    loop {
-        // Rate limiting delay between pages
-        let delay_ms = rand::thread_rng().gen_range(2000..4000);
-        sleep(Duration::from_millis(delay_ms)).await;
+    match config.options.timeline_wall.as_deref() {
+        None => {
+            state.wall_name = None;
+            download_timeline_pages(api, config, state, &creator_id, None).await?;
+        }
+        Some(selector) => {
+            let walls = api.get_creator_walls(&creator_id).await?;
+
+            let selected: Vec<_> = if selector.eq_ignore_ascii_case(ALL_WALLS) {
+                walls
+            } else {
+                walls
+                    .into_iter()
+                    .filter(|w| w.id == selector || w.display_name().eq_ignore_ascii_case(selector))
+                    .collect()
+            };
+
+            if selected.is_empty() {
+                return Err(Error::ConfigValidation {
+                    field: "timeline_wall".to_string(),
+                    message: format!(
+                        "No wall matching '{}' for {}",
+                        selector,
+                        state.creator_name.as_deref().unwrap_or("this creator")
+                    ),
+                });
+            }
+
+            for wall in &selected {
+                tracing::info!("Downloading wall: {}", wall.display_name());
+                state.wall_name = Some(wall.display_name().to_string());
+                download_timeline_pages(api, config, state, &creator_id, Some(&wall.id)).await?;
+            }
+
+            state.wall_name = None;
+        }
+    }
 
+    tracing::info!(
+        "Timeline download complete: {} pictures, {} videos",
+        state.pic_count,
+        state.vid_count
+    );
+
+    Ok(())
+}
+
+/// Page through a creator's timeline (or a single wall) and download all media.
+async fn download_timeline_pages(
+    api: &FanslyApi,
+    config: &Config,
+    state: &mut DownloadState,
+    creator_id: &str,
+    wall_id: Option<&str>,
+) -> Result<()> {
+    let mut cursor = "0".to_string();
+    let mut empty_response_count = 0;
+    let mut total_items = 0u64;
+
+    loop {
         // Fetch timeline page
-        let timeline = api.get_timeline(&creator_id, &cursor).await?;
+        let timeline = api
+            .get_timeline(
+                creator_id,
+                &cursor,
+                wall_id,
+                config.options.content_search.as_deref(),
+            )
+            .await?;
 
         // Extract media IDs
         let media_ids = extract_media_ids(&timeline.account_media, &timeline.account_media_bundles);
@@ -68,26 +132,18 @@ pub async fn download_timeline(
 
         // Fetch and download media in batches
         for chunk in media_ids.chunks(BATCH_SIZE) {
-            // Rate limiting delay between batches
-            let delay_ms = rand::thread_rng().gen_range(400..750);
-            sleep(Duration::from_millis(delay_ms)).await;
-
             let media_infos = api.get_media_info(chunk).await?;
 
             for media_info in &media_infos {
-                if let Some(item) =
-                    parse_media_info(media_info, config.options.download_media_previews)
-                {
+                if let Some(item) = select_media_item(config, media_info) {
                     let target_dir = get_download_path(config, state, &item)?;
 
-                    // Rate limiting delay between downloads
-                    let delay_ms = rand::thread_rng().gen_range(400..750);
-                    sleep(Duration::from_millis(delay_ms)).await;
-
-                    if let Err(e) =
-                        download_media_item(api, config, state, &item, &target_dir).await
-                    {
-                        tracing::warn!("Failed to download media {}: {}", item.media_id, e);
+                    match download_media_item(api, config, state, &item, &target_dir).await {
+                        Ok(_) => {}
+                        Err(Error::Interrupted) => return Err(Error::Interrupted),
+                        Err(e) => {
+                            tracing::warn!("Failed to download media {}: {}", item.media_id, e);
+                        }
                     }
                 }
             }
@@ -105,6 +161,16 @@ pub async fn download_timeline(
             }
         }
 
+        // Pages are newest first, so the rest is older than the date floor
+        if timeline
+            .posts
+            .last()
+            .is_some_and(|post| is_before_since(config, post.created_at))
+        {
+            tracing::debug!("Reached the configured since date, stopping timeline download");
+            break;
+        }
+
         // Get next cursor from last post
         cursor = timeline
             .posts
@@ -117,11 +183,5 @@ pub async fn download_timeline(
         }
     }
 
-    tracing::info!(
-        "Timeline download complete: {} pictures, {} videos",
-        state.pic_count,
-        state.vid_count
-    );
-
     Ok(())
 }
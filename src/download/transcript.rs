@@ -0,0 +1,420 @@
+//! Chat transcript export for message groups.
+//!
+//! Transcripts are written next to the downloaded message media as
+//! `transcript.json` and a standalone `transcript.html`, with attachment
+//! links relative to the transcript location.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::api::types::{Attachment, MediaBundle, Message, MessageGroup};
+use crate::error::Result;
+
+/// Transcript JSON filename.
+pub const TRANSCRIPT_JSON: &str = "transcript.json";
+
+/// Transcript HTML filename.
+pub const TRANSCRIPT_HTML: &str = "transcript.html";
+
+/// Exported conversation transcript.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Transcript {
+    pub group_id: String,
+    pub creator_id: String,
+    pub creator_username: String,
+    pub exported_at: String,
+    #[serde(default)]
+    pub participants: Vec<TranscriptParticipant>,
+    #[serde(default)]
+    pub messages: Vec<TranscriptMessage>,
+}
+
+/// A participant of the conversation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptParticipant {
+    pub user_id: String,
+    pub username: Option<String>,
+}
+
+/// A single message in the transcript.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptMessage {
+    pub id: String,
+    pub sender_id: String,
+    pub sender_username: Option<String>,
+    pub created_at: i64,
+    pub content: Option<String>,
+    #[serde(default)]
+    pub attachments: Vec<TranscriptAttachment>,
+}
+
+/// A media attachment of a message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptAttachment {
+    pub media_id: String,
+    /// Path relative to the transcript, if the media was downloaded.
+    pub path: Option<String>,
+}
+
+impl Transcript {
+    /// Create an empty transcript for a message group.
+    pub fn new(group: &MessageGroup, creator_id: &str, creator_username: &str) -> Self {
+        Self {
+            group_id: group.id.clone(),
+            creator_id: creator_id.to_string(),
+            creator_username: creator_username.to_string(),
+            exported_at: Utc::now().to_rfc3339(),
+            participants: group
+                .users
+                .iter()
+                .map(|u| TranscriptParticipant {
+                    user_id: u.user_id.clone(),
+                    username: u.username.clone(),
+                })
+                .collect(),
+            messages: Vec::new(),
+        }
+    }
+
+    /// Load a previously exported transcript, if one exists.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    /// Look up a participant's username by user ID.
+    fn username_for(&self, user_id: &str) -> Option<String> {
+        self.participants
+            .iter()
+            .find(|p| p.user_id == user_id)
+            .and_then(|p| p.username.clone())
+    }
+
+    /// Add a page of messages.
+    ///
+    /// `media_paths` maps media IDs to paths of downloaded files, which are
+    /// linked relative to `transcript_dir`.
+    pub fn add_messages(
+        &mut self,
+        messages: &[Message],
+        bundles: &[MediaBundle],
+        media_paths: &HashMap<String, PathBuf>,
+        transcript_dir: &Path,
+    ) {
+        for message in messages {
+            let attachments = attachment_media_ids(&message.attachments, bundles)
+                .into_iter()
+                .map(|media_id| TranscriptAttachment {
+                    path: media_paths
+                        .get(&media_id)
+                        .and_then(|p| relative_link(transcript_dir, p)),
+                    media_id,
+                })
+                .collect();
+
+            self.upsert(TranscriptMessage {
+                id: message.id.clone(),
+                sender_id: message.sender_id.clone(),
+                sender_username: self.username_for(&message.sender_id),
+                created_at: message.created_at,
+                content: message.content.clone(),
+                attachments,
+            });
+        }
+    }
+
+    /// Merge messages from a previous export, keeping newer data on conflict.
+    pub fn merge(&mut self, previous: Transcript) {
+        for message in previous.messages {
+            if !self.messages.iter().any(|m| m.id == message.id) {
+                self.messages.push(message);
+            }
+        }
+    }
+
+    /// Insert or replace a message by ID.
+    fn upsert(&mut self, message: TranscriptMessage) {
+        match self.messages.iter_mut().find(|m| m.id == message.id) {
+            Some(existing) => *existing = message,
+            None => self.messages.push(message),
+        }
+    }
+
+    /// Write the transcript as JSON and HTML into a directory.
+    pub fn write(&mut self, dir: &Path) -> Result<()> {
+        self.messages.sort_by(|a, b| {
+            a.created_at
+                .cmp(&b.created_at)
+                .then_with(|| a.id.cmp(&b.id))
+        });
+
+        fs::create_dir_all(dir)?;
+
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(dir.join(TRANSCRIPT_JSON), json)?;
+        fs::write(dir.join(TRANSCRIPT_HTML), self.to_html())?;
+
+        Ok(())
+    }
+
+    /// Render the transcript as a standalone HTML document.
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        let title = format!("Chat with {}", self.creator_username);
+
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str(&format!("<title>{}</title>\n", escape_html(&title)));
+        html.push_str(
+            "<style>\n\
+             body { font-family: sans-serif; max-width: 800px; margin: 2em auto; }\n\
+             .message { border-bottom: 1px solid #ddd; padding: 0.5em 0; }\n\
+             .meta { color: #666; font-size: 0.85em; }\n\
+             .content { white-space: pre-wrap; margin: 0.3em 0; }\n\
+             .attachments img, .attachments video { max-width: 320px; margin: 0.2em; }\n\
+             </style>\n",
+        );
+        html.push_str("</head>\n<body>\n");
+        html.push_str(&format!("<h1>{}</h1>\n", escape_html(&title)));
+        html.push_str(&format!(
+            "<p class=\"meta\">Exported {}</p>\n",
+            escape_html(&self.exported_at)
+        ));
+
+        for message in &self.messages {
+            let sender = message
+                .sender_username
+                .as_deref()
+                .unwrap_or(&message.sender_id);
+
+            html.push_str("<div class=\"message\">\n");
+            html.push_str(&format!(
+                "<div class=\"meta\"><strong>{}</strong> &middot; {}</div>\n",
+                escape_html(sender),
+                format_created_at(message.created_at)
+            ));
+
+            if let Some(content) = message.content.as_deref().filter(|c| !c.is_empty()) {
+                html.push_str(&format!(
+                    "<div class=\"content\">{}</div>\n",
+                    escape_html(content)
+                ));
+            }
+
+            if !message.attachments.is_empty() {
+                html.push_str("<div class=\"attachments\">\n");
+                for attachment in &message.attachments {
+                    html.push_str(&render_attachment(attachment));
+                }
+                html.push_str("</div>\n");
+            }
+
+            html.push_str("</div>\n");
+        }
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+/// Resolve the media IDs referenced by a message's attachments.
+/// Get the media IDs of message attachments, expanding bundles.
+pub(crate) fn attachment_media_ids(
+    attachments: &[Attachment],
+    bundles: &[MediaBundle],
+) -> Vec<String> {
+    let mut ids = Vec::new();
+
+    for attachment in attachments {
+        match attachment.content_type {
+            Attachment::ACCOUNT_MEDIA => ids.push(attachment.content_id.clone()),
+            Attachment::MEDIA_BUNDLE => {
+                if let Some(bundle) = bundles.iter().find(|b| b.id == attachment.content_id) {
+                    ids.extend(bundle.account_media_ids.iter().cloned());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    ids
+}
+
+/// Render a single attachment as HTML.
+fn render_attachment(attachment: &TranscriptAttachment) -> String {
+    let Some(path) = attachment.path.as_deref() else {
+        return format!(
+            "<span class=\"meta\">[media {} not downloaded]</span>\n",
+            escape_html(&attachment.media_id)
+        );
+    };
+
+    let href = escape_html(path);
+    let ext = path.rsplit('.').next().unwrap_or("").to_lowercase();
+
+    match ext.as_str() {
+        "jpg" | "jpeg" | "png" | "gif" | "webp" => {
+            format!(
+                "<a href=\"{0}\"><img src=\"{0}\" loading=\"lazy\"></a>\n",
+                href
+            )
+        }
+        "mp4" | "webm" | "mov" => format!("<video src=\"{}\" controls></video>\n", href),
+        "mp3" | "m4a" | "ogg" | "wav" => format!("<audio src=\"{}\" controls></audio>\n", href),
+        _ => format!("<a href=\"{0}\">{0}</a>\n", href),
+    }
+}
+
+/// Build a forward-slash link from a directory to a file inside it.
+fn relative_link(base: &Path, target: &Path) -> Option<String> {
+    let relative = target.strip_prefix(base).ok()?;
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    Some(parts.join("/"))
+}
+
+/// Format a message timestamp (seconds or milliseconds) for display.
+fn format_created_at(created_at: i64) -> String {
+    let timestamp_ms = if created_at < 1_000_000_000_000 {
+        created_at * 1000
+    } else {
+        created_at
+    };
+
+    match Utc.timestamp_millis_opt(timestamp_ms) {
+        chrono::LocalResult::Single(dt) => dt.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        _ => created_at.to_string(),
+    }
+}
+
+/// Escape text for safe inclusion in HTML.
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::types::GroupUser;
+
+    fn make_group() -> MessageGroup {
+        MessageGroup {
+            id: "group1".to_string(),
+            users: vec![GroupUser {
+                user_id: "100".to_string(),
+                username: Some("creator".to_string()),
+            }],
+        }
+    }
+
+    fn make_message(id: &str, created_at: i64, content: &str, media: &str) -> Message {
+        Message {
+            id: id.to_string(),
+            sender_id: "100".to_string(),
+            created_at,
+            content: Some(content.to_string()),
+            attachments: vec![Attachment {
+                content_id: media.to_string(),
+                content_type: Attachment::ACCOUNT_MEDIA,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_add_messages_links_relative_paths() {
+        let base = PathBuf::from("/downloads/creator_fansly/Messages");
+        let mut paths = HashMap::new();
+        paths.insert(
+            "m1".to_string(),
+            base.join("Pictures").join("2024_id_m1.jpg"),
+        );
+
+        let mut transcript = Transcript::new(&make_group(), "100", "creator");
+        transcript.add_messages(&[make_message("1", 10, "hi", "m1")], &[], &paths, &base);
+
+        let message = &transcript.messages[0];
+        assert_eq!(message.sender_username.as_deref(), Some("creator"));
+        assert_eq!(
+            message.attachments[0].path.as_deref(),
+            Some("Pictures/2024_id_m1.jpg")
+        );
+    }
+
+    #[test]
+    fn test_bundle_attachments_expand() {
+        let bundle = MediaBundle {
+            id: "b1".to_string(),
+            account_id: "100".to_string(),
+            account_media_ids: vec!["m1".to_string(), "m2".to_string()],
+            preview_id: None,
+            created_at: 0,
+        };
+        let attachments = vec![Attachment {
+            content_id: "b1".to_string(),
+            content_type: Attachment::MEDIA_BUNDLE,
+        }];
+
+        assert_eq!(
+            attachment_media_ids(&attachments, &[bundle]),
+            vec!["m1".to_string(), "m2".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_merge_keeps_previous_messages() {
+        let base = PathBuf::from("/tmp");
+        let mut old = Transcript::new(&make_group(), "100", "creator");
+        old.add_messages(
+            &[make_message("1", 10, "old", "m1")],
+            &[],
+            &HashMap::new(),
+            &base,
+        );
+
+        let mut new = Transcript::new(&make_group(), "100", "creator");
+        new.add_messages(
+            &[make_message("2", 20, "new", "m2")],
+            &[],
+            &HashMap::new(),
+            &base,
+        );
+        new.merge(old);
+
+        assert_eq!(new.messages.len(), 2);
+    }
+
+    #[test]
+    fn test_html_escapes_content() {
+        let mut transcript = Transcript::new(&make_group(), "100", "creator");
+        transcript.add_messages(
+            &[make_message("1", 10, "<script>alert(1)</script>", "m1")],
+            &[],
+            &HashMap::new(),
+            Path::new("/tmp"),
+        );
+
+        let html = transcript.to_html();
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(html.contains("not downloaded"));
+    }
+}
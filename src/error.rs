@@ -15,6 +15,9 @@ pub enum Error {
     #[error("Missing required configuration: {0}")]
     MissingConfig(String),
 
+    #[error("Vault error: {0}")]
+    Vault(String),
+
     // API errors
     #[error("API error: {0}")]
     Api(String),
@@ -28,6 +31,9 @@ pub enum Error {
     #[error("Rate limited, retry after {0} seconds")]
     RateLimited(u64),
 
+    #[error("Proxy error: {0}")]
+    Proxy(String),
+
     // Download errors
     #[error("Download failed: {0}")]
     Download(String),
@@ -38,6 +44,9 @@ pub enum Error {
     #[error("Duplicate threshold exceeded ({0} duplicates)")]
     DuplicateThreshold(u64),
 
+    #[error("Interrupted by shutdown request")]
+    Interrupted,
+
     // File system errors
     #[error("Invalid filename (path traversal attempt): {0}")]
     InvalidFilename(String),
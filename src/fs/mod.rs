@@ -3,12 +3,23 @@
 //! Provides:
 //! - Path and directory management
 //! - Filename generation and manipulation
+//! - Creator registry (username/account ID map) and rename handling
+//! - Statistics and integrity checks of downloaded files
 
 pub mod naming;
 pub mod paths;
+pub mod registry;
+pub mod scan;
 
 pub use naming::{
     has_hash_in_filename, inject_hash_into_filename, make_unique_filename, sanitize_filename,
     sanitize_path_component,
 };
-pub use paths::{ensure_dir, get_creator_folder, get_download_path};
+pub use paths::{
+    ensure_dir, get_creator_folder, get_creator_folder_for, get_download_path,
+    get_download_type_folder,
+};
+pub use registry::{
+    ensure_username_alias, migrate_renamed_creator, resolve_creators, CreatorRegistry,
+};
+pub use scan::{folder_stats, verify_directory, verify_file, BrokenFile, FolderStats};
@@ -2,7 +2,7 @@
 
 use std::path::PathBuf;
 
-use crate::config::{Config, DownloadType};
+use crate::config::{Config, DownloadType, FolderNaming};
 use crate::download::DownloadState;
 use crate::error::Result;
 use crate::fs::naming::sanitize_path_component;
@@ -14,19 +14,28 @@ pub fn get_download_path(
     state: &DownloadState,
     item: &MediaItem,
 ) -> Result<PathBuf> {
-    let base_dir = config.download_directory();
+    let mut path = get_download_type_folder(config, state)?;
 
-    // Build creator folder name with sanitization to prevent path traversal
-    let creator_name = state.creator_name.as_deref().unwrap_or("unknown_creator");
-    let sanitized_name = sanitize_path_component(creator_name)?;
+    // Add media type folder
+    path = path.join(item.media_type().folder_name());
 
-    let creator_folder = if config.options.use_folder_suffix {
-        format!("{}_fansly", sanitized_name)
-    } else {
-        sanitized_name
-    };
+    // Add previews subfolder if configured
+    if item.is_preview && config.options.separate_previews {
+        path = path.join("Previews");
+    }
+
+    Ok(path)
+}
 
-    let mut path = base_dir.join(&creator_folder);
+/// Get the folder for the current download type (before media type folders).
+pub fn get_download_type_folder(config: &Config, state: &DownloadState) -> Result<PathBuf> {
+    let mut path = match &state.base_path {
+        Some(base_path) => base_path.clone(),
+        None => {
+            let creator_name = state.creator_name.as_deref().unwrap_or("unknown_creator");
+            get_creator_folder(config, creator_name)?
+        }
+    };
 
     // Add download type folder if separated
     match state.download_type {
@@ -42,15 +51,17 @@ pub fn get_download_path(
         DownloadType::Single => {
             path = path.join("Single");
         }
+        DownloadType::Stories => {
+            path = path.join("Stories");
+        }
         _ => {}
     }
 
-    // Add media type folder
-    path = path.join(item.media_type().folder_name());
-
-    // Add previews subfolder if configured
-    if item.is_preview && config.options.separate_previews {
-        path = path.join("Previews");
+    // Add wall folder when downloading timeline walls separately
+    if state.download_type == DownloadType::Timeline {
+        if let Some(wall_name) = state.wall_name.as_deref() {
+            path = path.join(sanitize_path_component(wall_name)?);
+        }
     }
 
     Ok(path)
@@ -74,6 +85,18 @@ pub fn get_creator_folder(config: &Config, creator_name: &str) -> Result<PathBuf
     Ok(base_dir.join(&creator_folder))
 }
 
+/// Get the creator folder path, keyed by username or account ID as configured.
+pub fn get_creator_folder_for(
+    config: &Config,
+    username: &str,
+    account_id: &str,
+) -> Result<PathBuf> {
+    match config.options.folder_naming {
+        FolderNaming::Username => get_creator_folder(config, username),
+        FolderNaming::AccountId => get_creator_folder(config, account_id),
+    }
+}
+
 /// Ensure a directory exists, creating it if necessary.
 pub fn ensure_dir(path: &PathBuf) -> Result<()> {
     if !path.exists() {
@@ -85,20 +108,11 @@ pub fn ensure_dir(path: &PathBuf) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{AccountConfig, CacheConfig, Config, CreatorConfig, OptionsConfig};
-
-    fn make_test_config() -> Config {
-        Config {
-            targeted_creator: CreatorConfig::default(),
-            my_account: AccountConfig::default(),
-            options: OptionsConfig::default(),
-            cache: CacheConfig::default(),
-        }
-    }
+    use crate::config::Config;
 
     #[test]
     fn test_get_creator_folder() {
-        let mut config = make_test_config();
+        let mut config = Config::default();
         config.options.download_directory = Some(PathBuf::from("/downloads"));
         config.options.use_folder_suffix = true;
 
@@ -112,7 +126,7 @@ mod tests {
 
     #[test]
     fn test_get_creator_folder_path_traversal() {
-        let mut config = make_test_config();
+        let mut config = Config::default();
         config.options.download_directory = Some(PathBuf::from("/downloads"));
 
         // Path traversal should be rejected
@@ -122,7 +136,7 @@ mod tests {
 
     #[test]
     fn test_get_creator_folder_sanitizes_special_chars() {
-        let mut config = make_test_config();
+        let mut config = Config::default();
         config.options.download_directory = Some(PathBuf::from("/downloads"));
         config.options.use_folder_suffix = false;
 
@@ -130,4 +144,38 @@ mod tests {
         let path = get_creator_folder(&config, "user/name").unwrap();
         assert_eq!(path, PathBuf::from("/downloads/user_name"));
     }
+
+    #[test]
+    fn test_get_download_type_folder_with_wall() {
+        let mut config = Config::default();
+        config.options.download_directory = Some(PathBuf::from("/downloads"));
+
+        let mut state = DownloadState::new("testuser".to_string(), "1".to_string());
+        state.download_type = DownloadType::Timeline;
+        state.wall_name = Some("Behind/Scenes".to_string());
+
+        let path = get_download_type_folder(&config, &state).unwrap();
+        assert_eq!(
+            path,
+            PathBuf::from("/downloads/testuser_fansly/Timeline/Behind_Scenes")
+        );
+
+        // Wall folders only apply to timeline downloads
+        state.download_type = DownloadType::Messages;
+        let path = get_download_type_folder(&config, &state).unwrap();
+        assert_eq!(path, PathBuf::from("/downloads/testuser_fansly/Messages"));
+    }
+
+    #[test]
+    fn test_get_creator_folder_for_account_id() {
+        let mut config = Config::default();
+        config.options.download_directory = Some(PathBuf::from("/downloads"));
+
+        let path = get_creator_folder_for(&config, "testuser", "123456").unwrap();
+        assert_eq!(path, PathBuf::from("/downloads/testuser_fansly"));
+
+        config.options.folder_naming = FolderNaming::AccountId;
+        let path = get_creator_folder_for(&config, "testuser", "123456").unwrap();
+        assert_eq!(path, PathBuf::from("/downloads/123456_fansly"));
+    }
 }
@@ -0,0 +1,441 @@
+//! Persistent creator registry (account ID ↔ username) and rename handling.
+//!
+//! Usernames are resolved to account IDs once and remembered, so later runs can
+//! look creators up by their stable ID and detect when a creator was renamed.
+//! Creators are resolved in batches, by ID where known and by username otherwise.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::api::types::AccountInfo;
+use crate::api::{FanslyApi, BATCH_SIZE};
+use crate::config::{Config, FolderNaming, RenameAction};
+use crate::error::Result;
+use crate::fs::paths::get_creator_folder;
+
+/// Registry filename inside the state directory.
+pub const REGISTRY_FILE: &str = "creators.json";
+
+/// Persistent map of creator account IDs to usernames.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CreatorRegistry {
+    #[serde(default)]
+    creators: BTreeMap<String, RegistryEntry>,
+
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+/// Known names of a single creator account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryEntry {
+    pub username: String,
+    #[serde(default)]
+    pub previous_usernames: Vec<String>,
+    pub last_seen: String,
+}
+
+impl CreatorRegistry {
+    /// Load the registry from a file, or start an empty one if it doesn't exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut registry = if path.exists() {
+            let content = fs::read_to_string(path)?;
+            serde_json::from_str(&content)?
+        } else {
+            Self::default()
+        };
+        registry.path = Some(path.to_path_buf());
+        Ok(registry)
+    }
+
+    /// Load the registry from the configured state directory.
+    pub fn load_for(config: &Config) -> Result<Self> {
+        Self::load(&config.state_directory().join(REGISTRY_FILE))
+    }
+
+    /// Save the registry to the file it was loaded from.
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Look up a known account ID by current or previous username.
+    pub fn id_for_username(&self, username: &str) -> Option<&str> {
+        let username = username.trim_start_matches('@');
+
+        // Prefer a current username match over a previous one
+        self.creators
+            .iter()
+            .find(|(_, e)| e.username.eq_ignore_ascii_case(username))
+            .or_else(|| {
+                self.creators.iter().find(|(_, e)| {
+                    e.previous_usernames
+                        .iter()
+                        .any(|u| u.eq_ignore_ascii_case(username))
+                })
+            })
+            .map(|(id, _)| id.as_str())
+    }
+
+    /// Get the last known username of an account.
+    pub fn username_for_id(&self, account_id: &str) -> Option<&str> {
+        self.creators.get(account_id).map(|e| e.username.as_str())
+    }
+
+    /// Record the current username of an account.
+    ///
+    /// Returns the previous username if the creator was renamed.
+    pub fn record(&mut self, account_id: &str, username: &str) -> Option<String> {
+        let now = Utc::now().to_rfc3339();
+
+        match self.creators.get_mut(account_id) {
+            Some(entry) => {
+                entry.last_seen = now;
+                if entry.username == username {
+                    return None;
+                }
+
+                let previous = std::mem::replace(&mut entry.username, username.to_string());
+                entry.previous_usernames.retain(|u| u != username);
+                if !entry.previous_usernames.contains(&previous) {
+                    entry.previous_usernames.push(previous.clone());
+                }
+                Some(previous)
+            }
+            None => {
+                self.creators.insert(
+                    account_id.to_string(),
+                    RegistryEntry {
+                        username: username.to_string(),
+                        previous_usernames: Vec::new(),
+                        last_seen: now,
+                    },
+                );
+                None
+            }
+        }
+    }
+}
+
+/// Resolve the account info of all creators with as few requests as possible.
+///
+/// Creators with an account ID known from the registry are looked up by ID, which
+/// keeps working after a rename; the rest are looked up by username. Returns each
+/// requested name with its account, or `None` if it doesn't exist.
+pub async fn resolve_creators(
+    api: &FanslyApi,
+    registry: &CreatorRegistry,
+    creators: &[String],
+) -> Result<Vec<(String, Option<AccountInfo>)>> {
+    let known_ids: Vec<String> = creators
+        .iter()
+        .filter_map(|name| registry.id_for_username(name).map(str::to_string))
+        .collect();
+
+    let mut accounts = Vec::new();
+    for chunk in known_ids.chunks(BATCH_SIZE) {
+        accounts.extend(api.get_accounts_by_ids(chunk).await?);
+    }
+
+    let find = |accounts: &[AccountInfo], name: &str| -> Option<AccountInfo> {
+        let name = name.trim_start_matches('@');
+        let id = registry.id_for_username(name);
+        accounts
+            .iter()
+            .find(|a| Some(a.id.as_str()) == id || a.username.eq_ignore_ascii_case(name))
+            .cloned()
+    };
+
+    // Names whose cached ID is gone (or never known) are resolved by username
+    let unresolved: Vec<&str> = creators
+        .iter()
+        .filter(|name| find(&accounts, name).is_none())
+        .map(String::as_str)
+        .collect();
+    if !unresolved.is_empty() {
+        accounts.extend(api.get_creator_accounts(&unresolved).await?);
+    }
+
+    Ok(creators
+        .iter()
+        .map(|name| (name.clone(), find(&accounts, name)))
+        .collect())
+}
+
+/// Migrate the folder of a renamed creator according to the configured action.
+///
+/// Only applies to username-keyed folders; ID-keyed folders never change.
+pub fn migrate_renamed_creator(
+    config: &Config,
+    old_username: &str,
+    new_username: &str,
+) -> Result<()> {
+    if config.options.folder_naming != FolderNaming::Username {
+        return Ok(());
+    }
+
+    let old_folder = get_creator_folder(config, old_username)?;
+    let new_folder = get_creator_folder(config, new_username)?;
+
+    if !old_folder.exists() || old_folder == new_folder {
+        return Ok(());
+    }
+
+    if new_folder.exists() || new_folder.is_symlink() {
+        tracing::warn!(
+            "Both {} and {} exist, not migrating renamed creator folder",
+            old_folder.display(),
+            new_folder.display()
+        );
+        return Ok(());
+    }
+
+    match config.options.on_rename {
+        RenameAction::Move => {
+            fs::rename(&old_folder, &new_folder)?;
+            tracing::info!("Moved {} to {}", old_folder.display(), new_folder.display());
+        }
+        RenameAction::Symlink => {
+            symlink_dir(&old_folder, &new_folder)?;
+            tracing::info!(
+                "Linked {} to {}",
+                new_folder.display(),
+                old_folder.display()
+            );
+        }
+        RenameAction::Ignore => {}
+    }
+
+    Ok(())
+}
+
+/// Ensure the username alias of an ID-keyed creator folder points to it.
+///
+/// Stale aliases from previous usernames are removed.
+pub fn ensure_username_alias(
+    config: &Config,
+    username: &str,
+    account_id: &str,
+    previous_username: Option<&str>,
+) -> Result<()> {
+    if config.options.folder_naming != FolderNaming::AccountId {
+        return Ok(());
+    }
+
+    let target = get_creator_folder(config, account_id)?;
+    fs::create_dir_all(&target)?;
+
+    if let Some(previous) = previous_username {
+        let stale = get_creator_folder(config, previous)?;
+        if stale.is_symlink() {
+            fs::remove_file(&stale)?;
+        }
+    }
+
+    let alias = get_creator_folder(config, username)?;
+    if alias == target || alias.exists() || alias.is_symlink() {
+        return Ok(());
+    }
+
+    symlink_dir(&target, &alias)?;
+    Ok(())
+}
+
+/// Create a directory symlink at `link` pointing to `target`.
+#[cfg(unix)]
+fn symlink_dir(target: &Path, link: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(target, link)?;
+    Ok(())
+}
+
+/// Create a directory symlink at `link` pointing to `target`.
+#[cfg(windows)]
+fn symlink_dir(target: &Path, link: &Path) -> Result<()> {
+    std::os::windows::fs::symlink_dir(target, link)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::stub::spawn_server;
+
+    fn make_test_config(dir: &Path) -> Config {
+        let mut config = Config::default();
+        config.options.download_directory = Some(dir.to_path_buf());
+        config
+    }
+
+    #[test]
+    fn test_record_detects_rename() {
+        let mut registry = CreatorRegistry::default();
+
+        assert_eq!(registry.record("1", "oldname"), None);
+        assert_eq!(registry.record("1", "oldname"), None);
+        assert_eq!(registry.record("1", "newname"), Some("oldname".to_string()));
+
+        assert_eq!(registry.username_for_id("1"), Some("newname"));
+        assert_eq!(registry.id_for_username("newname"), Some("1"));
+        assert_eq!(registry.id_for_username("OldName"), Some("1"));
+        assert_eq!(registry.id_for_username("unknown"), None);
+    }
+
+    #[test]
+    fn test_registry_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join(REGISTRY_FILE);
+
+        let mut registry = CreatorRegistry::load(&path).unwrap();
+        registry.record("1", "creator");
+        registry.save().unwrap();
+
+        let registry = CreatorRegistry::load(&path).unwrap();
+        assert_eq!(registry.id_for_username("creator"), Some("1"));
+    }
+
+    #[test]
+    fn test_migrate_renamed_creator_moves_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = make_test_config(dir.path());
+
+        fs::create_dir_all(dir.path().join("oldname_fansly").join("Pictures")).unwrap();
+        migrate_renamed_creator(&config, "oldname", "newname").unwrap();
+
+        assert!(!dir.path().join("oldname_fansly").exists());
+        assert!(dir.path().join("newname_fansly").join("Pictures").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_migrate_renamed_creator_symlinks_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = make_test_config(dir.path());
+        config.options.on_rename = RenameAction::Symlink;
+
+        fs::create_dir_all(dir.path().join("oldname_fansly")).unwrap();
+        migrate_renamed_creator(&config, "oldname", "newname").unwrap();
+
+        assert!(dir.path().join("oldname_fansly").is_dir());
+        assert!(dir.path().join("newname_fansly").is_symlink());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_ensure_username_alias_replaces_stale_alias() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = make_test_config(dir.path());
+        config.options.folder_naming = FolderNaming::AccountId;
+
+        ensure_username_alias(&config, "oldname", "123", None).unwrap();
+        assert!(dir.path().join("oldname_fansly").is_symlink());
+
+        ensure_username_alias(&config, "newname", "123", Some("oldname")).unwrap();
+        assert!(!dir.path().join("oldname_fansly").exists());
+        assert!(dir.path().join("newname_fansly").is_symlink());
+        assert!(dir.path().join("123_fansly").is_dir());
+    }
+
+    /// Answer account lookups: IDs belong to creators renamed to `new-<id>`,
+    /// usernames to accounts `id-<username>`, except `ghost` which doesn't exist.
+    fn lookup_accounts(path: &str) -> (u16, String) {
+        let query = |key: &str| {
+            let (_, list) = path.split_once(key)?;
+            let list = list.split('&').next().unwrap_or_default();
+            Some(list.split(',').map(str::to_string).collect::<Vec<_>>())
+        };
+        let accounts: Vec<String> = if let Some(ids) = query("?ids=") {
+            ids.iter()
+                .map(|id| format!(r#"{{"id":"{}","username":"new-{}"}}"#, id, id))
+                .collect()
+        } else if let Some(usernames) = query("?usernames=") {
+            usernames
+                .iter()
+                .filter(|name| *name != "ghost")
+                .map(|name| format!(r#"{{"id":"id-{}","username":"{}"}}"#, name, name))
+                .collect()
+        } else {
+            return (404, "{}".to_string());
+        };
+        (
+            200,
+            format!(r#"{{"success":true,"response":[{}]}}"#, accounts.join(",")),
+        )
+    }
+
+    async fn stub_api() -> (FanslyApi, crate::api::stub::Requests) {
+        let (url, requests) = spawn_server(lookup_accounts).await;
+        let api = FanslyApi::builder("token")
+            .session_id("session")
+            .device_id("device", None)
+            .base_url(url)
+            .build()
+            .await
+            .unwrap();
+        (api, requests)
+    }
+
+    #[tokio::test]
+    async fn test_resolve_creators_by_id_then_username() {
+        let (api, requests) = stub_api().await;
+        let mut registry = CreatorRegistry::default();
+        registry.record("10", "oldname");
+
+        let names = ["oldname", "alice", "ghost"].map(String::from);
+        let resolved = resolve_creators(&api, &registry, &names).await.unwrap();
+
+        // The renamed creator is found by its cached ID
+        assert_eq!(resolved[0].0, "oldname");
+        let renamed = resolved[0].1.as_ref().unwrap();
+        assert_eq!(
+            (renamed.id.as_str(), renamed.username.as_str()),
+            ("10", "new-10")
+        );
+
+        assert_eq!(resolved[1].1.as_ref().unwrap().id, "id-alice");
+        assert_eq!(resolved[2].0, "ghost");
+        assert!(resolved[2].1.is_none());
+
+        // Only the names without a cached account are looked up by username
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].starts_with("get /api/v1/account?ids=10&"));
+        assert!(requests[1].starts_with("get /api/v1/account?usernames=alice,ghost&"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_creators_batches_requests() {
+        let (api, requests) = stub_api().await;
+        let mut registry = CreatorRegistry::default();
+        let mut names = Vec::new();
+        for i in 0..=BATCH_SIZE {
+            registry.record(&i.to_string(), &format!("known{}", i));
+            names.push(format!("known{}", i));
+            names.push(format!("new{}", i));
+        }
+
+        let resolved = resolve_creators(&api, &registry, &names).await.unwrap();
+        assert_eq!(resolved.len(), names.len());
+        assert!(resolved.iter().all(|(_, account)| account.is_some()));
+
+        let requests = requests.lock().unwrap();
+        let count = |key: &str| requests.iter().filter(|r| r.contains(key)).count();
+        assert_eq!(count("?ids="), 2);
+        assert_eq!(count("?usernames="), 2);
+        for request in requests.iter() {
+            let (_, list) = request.split_once('=').unwrap();
+            let list = list.split('&').next().unwrap();
+            assert!(list.split(',').count() <= BATCH_SIZE);
+        }
+    }
+}
@@ -0,0 +1,305 @@
+//! Scanning of downloaded files: statistics and integrity checks.
+
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::error::Result;
+use crate::media::MediaType;
+
+/// File counts and sizes of a folder, by media type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FolderStats {
+    pub pictures: u64,
+    pub videos: u64,
+    pub audio: u64,
+    pub bytes: u64,
+}
+
+impl FolderStats {
+    /// Get the number of media files.
+    pub fn total(&self) -> u64 {
+        self.pictures + self.videos + self.audio
+    }
+
+    /// Add the counts of another folder.
+    pub fn add(&mut self, other: &FolderStats) {
+        self.pictures += other.pictures;
+        self.videos += other.videos;
+        self.audio += other.audio;
+        self.bytes += other.bytes;
+    }
+}
+
+/// A downloaded file that failed the integrity check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokenFile {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+/// Get the media type of a downloaded file from its extension.
+pub fn media_type_of(path: &Path) -> MediaType {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    match extension.as_str() {
+        "jpg" | "jpeg" | "png" | "gif" | "webp" => MediaType::Image,
+        "mp4" | "m4v" | "mov" | "webm" => MediaType::Video,
+        "mp3" | "m4a" | "aac" | "ogg" | "wav" => MediaType::Audio,
+        _ => MediaType::Unknown,
+    }
+}
+
+/// List the media files under a directory, skipping hidden entries such as
+/// the `.fansly` state directory.
+pub fn media_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if !dir.is_dir() {
+        return Ok(files);
+    }
+
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+
+            // Username aliases are symlinks to other creator folders
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if file_type.is_file() && media_type_of(&path) != MediaType::Unknown {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Count the media files under a directory.
+pub fn folder_stats(dir: &Path) -> Result<FolderStats> {
+    let mut stats = FolderStats::default();
+
+    for path in media_files(dir)? {
+        match media_type_of(&path) {
+            MediaType::Image => stats.pictures += 1,
+            MediaType::Video => stats.videos += 1,
+            MediaType::Audio => stats.audio += 1,
+            MediaType::Unknown => continue,
+        }
+        stats.bytes += fs::metadata(&path)?.len();
+    }
+
+    Ok(stats)
+}
+
+/// Check every media file under a directory, returning the number of files
+/// checked and the broken ones.
+pub fn verify_directory(dir: &Path) -> Result<(usize, Vec<BrokenFile>)> {
+    let files = media_files(dir)?;
+    let broken = files
+        .iter()
+        .filter_map(|path| {
+            verify_file(path).map(|reason| BrokenFile {
+                path: path.clone(),
+                reason,
+            })
+        })
+        .collect();
+
+    Ok((files.len(), broken))
+}
+
+/// Check a single file, returning why it's broken (if it is).
+///
+/// Images are fully decoded; MP4-based files must have a complete box
+/// structure with a `moov` box, which catches interrupted downloads.
+pub fn verify_file(path: &Path) -> Option<String> {
+    let len = match fs::metadata(path) {
+        Ok(metadata) => metadata.len(),
+        Err(e) => return Some(format!("unreadable: {}", e)),
+    };
+    if len == 0 {
+        return Some("empty file".to_string());
+    }
+
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    match extension.as_str() {
+        "mp4" | "m4v" | "mov" | "m4a" => check_mp4(path, len).err(),
+        _ if media_type_of(path) == MediaType::Image => {
+            let decoded = image::ImageReader::open(path)
+                .and_then(|reader| reader.with_guessed_format())
+                .map_err(|e| e.to_string())
+                .and_then(|reader| reader.decode().map_err(|e| e.to_string()));
+            decoded.err().map(|e| format!("invalid image: {}", e))
+        }
+        _ => None,
+    }
+}
+
+/// Walk the top-level MP4 boxes and check they add up to the file size.
+fn check_mp4(path: &Path, len: u64) -> std::result::Result<(), String> {
+    let mut file = File::open(path).map_err(|e| format!("unreadable: {}", e))?;
+    let mut offset = 0u64;
+    let mut has_moov = false;
+
+    while offset < len {
+        let mut header = [0u8; 8];
+        file.seek(SeekFrom::Start(offset))
+            .and_then(|_| file.read_exact(&mut header))
+            .map_err(|_| format!("truncated at byte {}", offset))?;
+
+        let mut size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as u64;
+        let box_type = &header[4..8];
+        if offset == 0 && box_type != b"ftyp" {
+            return Err("not an MP4 file".to_string());
+        }
+
+        match size {
+            // Box extends to the end of the file
+            0 => size = len - offset,
+            // 64-bit size follows the header
+            1 => {
+                let mut large = [0u8; 8];
+                file.read_exact(&mut large)
+                    .map_err(|_| format!("truncated at byte {}", offset))?;
+                size = u64::from_be_bytes(large);
+            }
+            _ => {}
+        }
+
+        // A corrupt 64-bit size can overflow the offset
+        let end = match offset.checked_add(size) {
+            Some(end) if size >= 8 => end,
+            _ => return Err(format!("invalid box at byte {}", offset)),
+        };
+        if end > len {
+            return Err(format!(
+                "truncated: box at byte {} needs {} bytes, file has {}",
+                offset,
+                size,
+                len - offset
+            ));
+        }
+
+        has_moov |= box_type == b"moov";
+        offset = end;
+    }
+
+    if has_moov {
+        Ok(())
+    } else {
+        Err("missing moov box (incomplete download)".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build an MP4 box.
+    fn mp4_box(kind: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut data = ((payload.len() + 8) as u32).to_be_bytes().to_vec();
+        data.extend_from_slice(kind);
+        data.extend_from_slice(payload);
+        data
+    }
+
+    fn valid_mp4() -> Vec<u8> {
+        let mut data = mp4_box(b"ftyp", b"isom\0\0\0\x01");
+        data.extend(mp4_box(b"moov", &[0; 16]));
+        data.extend(mp4_box(b"mdat", &[1; 64]));
+        data
+    }
+
+    #[test]
+    fn test_verify_mp4() {
+        let dir = tempfile::tempdir().unwrap();
+        let good = dir.path().join("good.mp4");
+        fs::write(&good, valid_mp4()).unwrap();
+        assert_eq!(verify_file(&good), None);
+
+        let truncated = dir.path().join("truncated.mp4");
+        let data = valid_mp4();
+        fs::write(&truncated, &data[..data.len() - 10]).unwrap();
+        assert!(verify_file(&truncated).unwrap().starts_with("truncated"));
+
+        let no_moov = dir.path().join("no_moov.mp4");
+        let mut data = mp4_box(b"ftyp", b"isom\0\0\0\x01");
+        data.extend(mp4_box(b"mdat", &[1; 64]));
+        fs::write(&no_moov, data).unwrap();
+        assert!(verify_file(&no_moov).unwrap().contains("moov"));
+
+        let empty = dir.path().join("empty.mp4");
+        fs::write(&empty, b"").unwrap();
+        assert_eq!(verify_file(&empty).as_deref(), Some("empty file"));
+
+        // A 64-bit box size that would overflow the offset
+        let huge = dir.path().join("huge.mp4");
+        let mut data = mp4_box(b"ftyp", b"isom\0\0\0\x01");
+        data.extend(1u32.to_be_bytes());
+        data.extend(b"mdat");
+        data.extend((u64::MAX - 8).to_be_bytes());
+        fs::write(&huge, data).unwrap();
+        assert_eq!(
+            verify_file(&huge).as_deref(),
+            Some("invalid box at byte 16")
+        );
+    }
+
+    #[test]
+    fn test_verify_image() {
+        let dir = tempfile::tempdir().unwrap();
+        let good = dir.path().join("good.png");
+        image::RgbImage::new(2, 2).save(&good).unwrap();
+        assert_eq!(verify_file(&good), None);
+
+        let data = fs::read(&good).unwrap();
+        let broken = dir.path().join("broken.png");
+        fs::write(&broken, &data[..data.len() / 2]).unwrap();
+        assert!(verify_file(&broken).unwrap().starts_with("invalid image"));
+    }
+
+    #[test]
+    fn test_folder_stats_and_verify_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let pictures = dir.path().join("Pictures");
+        let videos = dir.path().join("Timeline").join("Videos");
+        let state = dir.path().join(".fansly");
+        for d in [&pictures, &videos, &state] {
+            fs::create_dir_all(d).unwrap();
+        }
+
+        image::RgbImage::new(2, 2)
+            .save(pictures.join("a.png"))
+            .unwrap();
+        fs::write(videos.join("b.mp4"), valid_mp4()).unwrap();
+        fs::write(videos.join("c.mp4"), b"").unwrap();
+        fs::write(dir.path().join("profile.json"), b"{}").unwrap();
+        fs::write(state.join("hidden.mp4"), b"").unwrap();
+
+        let stats = folder_stats(dir.path()).unwrap();
+        assert_eq!(stats.pictures, 1);
+        assert_eq!(stats.videos, 2);
+        assert_eq!(stats.total(), 3);
+
+        let (checked, broken) = verify_directory(dir.path()).unwrap();
+        assert_eq!(checked, 3);
+        assert_eq!(broken.len(), 1);
+        assert!(broken[0].path.ends_with("c.mp4"));
+    }
+}
@@ -8,12 +8,17 @@
 //! - Download direct messages
 //! - Download single posts
 //! - Download purchased collections
+//! - Download active stories
 //! - Automatic deduplication via file hashing
 //! - M3U8/HLS video support
 //! - Rate limiting and retry logic
 //!
 //! # Example
 //!
+//! [`FanslyApi::new`] opens a WebSocket session and builds its own HTTP client;
+//! use [`FanslyApi::builder`] to provide a client, an existing session ID or
+//! another base URL instead.
+//!
 //! ```no_run
 //! use std::path::Path;
 //! use fansly_downloader::{Config, FanslyApi};
@@ -37,7 +42,9 @@
 pub mod api;
 pub mod cli;
 pub mod config;
+pub mod daemon;
 pub mod dedup;
+pub mod doctor;
 pub mod download;
 pub mod error;
 pub mod fs;
@@ -45,11 +52,11 @@ pub mod media;
 pub mod output;
 
 // Re-exports for convenience
-pub use api::FanslyApi;
+pub use api::{FanslyApi, FanslyApiBuilder};
 pub use config::{Config, DownloadMode};
 pub use download::{
-    download_collections, download_messages, download_single_post, download_timeline,
-    DownloadState, GlobalState,
+    download_collections, download_messages, download_single_post, download_stories,
+    download_timeline, DownloadState, GlobalState,
 };
 pub use error::{Error, Result};
 pub use media::{MediaItem, MediaType};
@@ -1,23 +1,44 @@
 //! Fansly Downloader RS - CLI entry point.
 
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::process::ExitCode;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 use clap::Parser;
+use console::Term;
+use futures::stream::{self, StreamExt};
+use tracing::Instrument;
 use tracing_subscriber::{fmt, EnvFilter};
 
 use fansly_downloader::{
-    api::FanslyApi,
-    cli::Args,
-    config::{parse_post_id, validate_config, Config, DownloadMode, DownloadType},
+    api::{types::AccountInfo, FanslyApi, WsEvent, BATCH_SIZE},
+    cli::{Args, Command, ConfigCommand, ListTarget, VaultCommand},
+    config::{
+        find_config_file, import_auth_file, validate_account, validate_config, validate_options,
+        vault::{NEW_PASSPHRASE_ENV, PASSPHRASE_ENV, TOKEN_KEY, VAULT_FILE},
+        Config, ConfigEditor, DownloadMode, DownloadType, Vault,
+    },
+    daemon::{wall_clock, DaemonState, DaemonStatus, Schedule},
+    doctor::{check_config, check_connectivity, check_environment, Status},
+    download::failures::FAILURES_FILE,
     download::{
-        download_collections, download_messages, download_single_post, download_timeline,
-        DownloadState, GlobalState,
+        backup_profile, download_collections, download_media_item, download_message,
+        download_messages, download_posts, download_single_post, download_stories,
+        download_timeline, fetch_posts_by_creator, is_shutdown_requested, request_shutdown,
+        shutdown_requested, DownloadState, FailedDownload, FailedDownloads, GlobalState,
     },
     error::{exit_codes, Error, Result},
-    fs::get_creator_folder,
+    fs::{
+        ensure_username_alias, folder_stats, get_creator_folder, get_creator_folder_for,
+        migrate_renamed_creator, resolve_creators, verify_directory, CreatorRegistry, FolderStats,
+    },
+    media::parse_media_info_capped,
     output::{
-        print_banner, print_config_summary, print_creator_stats, print_error, print_global_stats,
-        print_info, print_warning,
+        print_banner, print_checks, print_config_summary, print_creator_stats, print_error,
+        print_folder_stats, print_global_stats, print_info, print_rate_limit_stats, print_success,
+        print_warning, ProgressWriter,
     },
 };
 
@@ -28,12 +49,14 @@ async fn main() -> ExitCode {
         Err(e) => {
             print_error(&format!("{}", e));
             match e {
-                Error::Config(_) | Error::ConfigValidation { .. } | Error::MissingConfig(_) => {
-                    ExitCode::from(exit_codes::CONFIG_ERROR as u8)
-                }
-                Error::Authentication(_) | Error::Api(_) | Error::AccountNotFound(_) => {
-                    ExitCode::from(exit_codes::API_ERROR as u8)
-                }
+                Error::Config(_)
+                | Error::ConfigValidation { .. }
+                | Error::MissingConfig(_)
+                | Error::Vault(_) => ExitCode::from(exit_codes::CONFIG_ERROR as u8),
+                Error::Authentication(_)
+                | Error::Api(_)
+                | Error::AccountNotFound(_)
+                | Error::Proxy(_) => ExitCode::from(exit_codes::API_ERROR as u8),
                 Error::Download(_) | Error::M3U8(_) => {
                     ExitCode::from(exit_codes::DOWNLOAD_ERROR as u8)
                 }
@@ -51,13 +74,18 @@ async fn run() -> Result<()> {
     let log_level = if args.debug { "debug" } else { "info" };
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(log_level));
 
-    fmt().with_env_filter(filter).with_target(false).init();
+    fmt()
+        .with_env_filter(filter)
+        .with_target(false)
+        .with_writer(ProgressWriter)
+        .init();
 
     // Print banner
     print_banner();
+    args.validate()?;
 
     // Load configuration
-    let config_path = args.config.clone();
+    let config_path = find_config_file(args.config.as_deref());
     let mut config = if config_path.exists() {
         Config::load(&config_path)?
     } else {
@@ -66,38 +94,72 @@ async fn run() -> Result<()> {
             config_path.display()
         ));
         print_info("Using default configuration with CLI arguments");
-        Config {
-            targeted_creator: Default::default(),
-            my_account: Default::default(),
-            options: Default::default(),
-            cache: Default::default(),
-        }
+        Config::default()
     };
 
+    // Environment overrides apply before anything reads paths from the config
+    config.prepare(args.profile.as_deref(), std::env::vars())?;
+    if let Some(profile) = &args.profile {
+        print_info(&format!("Using profile: {}", profile));
+    }
+
+    let command = args.resolved_command();
+    if let Command::ImportAuth { file } = &command {
+        return import_auth(file, config, args.profile.as_deref(), &config_path);
+    }
+
+    if config.my_account.vault.is_some() {
+        config.unlock_vault(&vault_passphrase()?)?;
+    }
+
     // Merge CLI arguments into config
     args.merge_into_config(&mut config);
 
-    // Validate configuration
-    validate_config(&config)?;
+    // Commands that work without connecting
+    match &command {
+        Command::Config { action } => return run_config_command(&config, &config_path, *action),
+        Command::Vault { action } => return run_vault_command(&mut config, &config_path, action),
+        Command::Doctor { offline } => return run_doctor(&config, *offline).await,
+        Command::Verify { delete } => return verify_downloads(&config, *delete),
+        Command::Stats => return show_download_stats(&config),
+        _ => {}
+    }
+
+    // Validate configuration (retrying failed downloads and watching followed
+    // creators need no creators)
+    match command {
+        Command::RetryFailed => validate_account(&config)?,
+        Command::Watch { following: true } if config.targeted_creator.usernames().is_empty() => {
+            validate_account(&config)?;
+            validate_options(&config)?;
+        }
+        _ => validate_config(&config)?,
+    }
+
+    // Single post mode derives creators from the posts
+    let post_ids = if matches!(command, Command::Download(_))
+        && config.options.download_mode == DownloadMode::Single
+    {
+        Some(config.single_post_ids()?)
+    } else {
+        None
+    };
 
     // Print configuration summary
-    let creators: Vec<String> = config.targeted_creator.usernames.iter().cloned().collect();
+    let creators = config.targeted_creator.usernames();
+    let creators_summary = match &post_ids {
+        Some(ids) => vec![format!("(from {} post(s))", ids.len())],
+        None => creators.clone(),
+    };
     print_config_summary(
-        &creators,
+        &creators_summary,
         &config.options.download_mode.to_string(),
         &config.download_directory().display().to_string(),
     );
 
     // Initialize API client
     print_info("Connecting to Fansly...");
-    let api = FanslyApi::new(
-        config.my_account.authorization_token.clone(),
-        config.my_account.user_agent.clone(),
-        config.my_account.check_key.clone(),
-        config.cache.device_id.clone(),
-        config.cache.device_id_timestamp,
-    )
-    .await?;
+    let api = config.build_api().await?;
 
     // Validate token by fetching account info
     let account_info = api.get_client_account_info().await?;
@@ -110,33 +172,93 @@ async fn run() -> Result<()> {
     ));
 
     // Update cached device ID
-    let device_id = api.get_device_id().await?;
-    let device_id_timestamp = api.get_device_id_timestamp().await;
-    if let Some(timestamp) = device_id_timestamp {
-        config.update_cache(device_id, timestamp, Some(&config_path))?;
+    save_device_id(&api, &mut config).await?;
+
+    let mut registry = CreatorRegistry::load_for(&config)?;
+
+    match command {
+        Command::List {
+            target: ListTarget::Walls,
+        } => return list_creator_walls(&api, &registry, &creators).await,
+        Command::List {
+            target: ListTarget::Creators,
+        } => return list_creators(&api, &config, &registry, &creators).await,
+        Command::Watch { following } => {
+            return watch_creators(&api, &config, registry, &creators, following).await;
+        }
+        Command::Daemon { .. } => {
+            return run_daemon(&api, &mut config, registry, &creators).await;
+        }
+        _ => {}
     }
 
     // Initialize global state
     let mut global_state = GlobalState::default();
 
-    // Process each creator
-    for creator_name in &creators {
-        print_info(&format!("Processing creator: {}", creator_name));
+    if matches!(command, Command::RetryFailed) {
+        retry_failed(&api, &config, &mut global_state).await?;
+    } else if let Some(post_ids) = &post_ids {
+        process_single_posts(&api, &config, post_ids, &mut registry, &mut global_state).await?;
+    } else {
+        // Resolve all creators up front and report unknown usernames before downloading
+        let resolved = resolve_creators(&api, &registry, &creators).await?;
 
-        match process_creator(&api, &config, creator_name).await {
-            Ok(state) => {
-                print_creator_stats(&state);
-                global_state.add_creator_stats(&state);
+        let mut accounts = Vec::new();
+        for (creator_name, account) in resolved {
+            match account {
+                Some(account) => accounts.push((config.for_creator(&creator_name), account)),
+                None => {
+                    print_error(&format!("Creator not found: {}", creator_name));
+                    global_state.mark_creator_failed();
+                }
             }
-            Err(e) => {
-                print_error(&format!("Failed to process {}: {}", creator_name, e));
-                global_state.mark_creator_failed();
+        }
+
+        // Process creators, up to `max_concurrent_creators` at a time
+        let registry = Mutex::new(registry);
+        let mut results = stream::iter(&accounts)
+            .map(|(creator_config, creator_info)| {
+                let span = tracing::info_span!("creator", name = %creator_info.username);
+                let (api, config, registry) = (&api, creator_config, &registry);
+                async move {
+                    print_info(&format!("Processing creator: {}", creator_info.username));
+                    let result = process_creator(api, config, registry, creator_info).await;
+                    (creator_info, result)
+                }
+                .instrument(span)
+            })
+            .buffer_unordered(config.options.max_concurrent_creators);
+
+        // Results are aggregated here, one at a time
+        while let Some((creator_info, result)) = results.next().await {
+            match result {
+                Ok(state) => {
+                    print_creator_stats(&state);
+                    save_failures(&config, &state);
+                    global_state.add_creator_stats(&state);
+                }
+                Err(e) => {
+                    print_error(&format!(
+                        "Failed to process {}: {}",
+                        creator_info.username, e
+                    ));
+                    global_state.mark_creator_failed();
+                }
             }
         }
     }
 
+    // The device ID may have been refreshed during the run
+    if let Err(e) = save_device_id(&api, &mut config).await {
+        print_warning(&format!("Failed to save device ID: {}", e));
+    }
+
     // Print global statistics
     print_global_stats(&global_state);
+    if config.options.show_downloads {
+        let (api_metrics, cdn_metrics) = api.rate_limit_metrics();
+        print_rate_limit_stats(&api_metrics, &cdn_metrics);
+    }
 
     if global_state.creators_failed > 0 {
         return Err(Error::Api(format!(
@@ -148,27 +270,988 @@ async fn run() -> Result<()> {
     Ok(())
 }
 
+/// Import credentials from a browser export and save them to the config file.
+fn import_auth(
+    file: &Path,
+    config: Config,
+    profile: Option<&str>,
+    config_path: &Path,
+) -> Result<()> {
+    let imported = import_auth_file(file)?;
+
+    // The profile is already selected
+    let mut selected = config;
+    if selected.my_account.vault.is_some() {
+        selected.unlock_vault(&vault_passphrase()?)?;
+    }
+    imported.apply_to(&mut selected);
+    validate_account(&selected)?;
+
+    // Only the imported values change in the file; the token goes to the
+    // vault if the account has one
+    let mut editor = ConfigEditor::open(config_path)?;
+    let account = &selected.my_account;
+    if let Some(vault) = &mut selected.vault {
+        if imported.authorization_token.is_some() {
+            vault.set(TOKEN_KEY, account.authorization_token.as_str());
+        }
+    } else if imported.authorization_token.is_some() {
+        let token = account.authorization_token.as_str();
+        editor.set(profile, "my_account", "authorization_token", token)?;
+    }
+    if imported.user_agent.is_some() {
+        editor.set(
+            profile,
+            "my_account",
+            "user_agent",
+            account.user_agent.as_str(),
+        )?;
+    }
+    editor.save()?;
+    if imported.device_id.is_some() || selected.vault.is_some() {
+        selected.save_device_cache()?;
+    }
+
+    let found = [
+        (
+            "authorization token",
+            imported.authorization_token.is_some(),
+        ),
+        ("user agent", imported.user_agent.is_some()),
+        ("device ID", imported.device_id.is_some()),
+    ];
+    for (name, present) in found {
+        if present {
+            print_info(&format!("Imported {}", name));
+        } else {
+            print_warning(&format!("No {} found, keeping existing value", name));
+        }
+    }
+
+    print_success(&format!("Credentials saved to {}", config_path.display()));
+    if let Some(vault) = &selected.vault {
+        print_info(&format!(
+            "Secrets stored in vault {}",
+            vault.path().display()
+        ));
+    }
+    Ok(())
+}
+
+/// Create the account's vault, or change its passphrase.
+fn run_vault_command(config: &mut Config, config_path: &Path, action: &VaultCommand) -> Result<()> {
+    match action {
+        VaultCommand::Init { file } => {
+            if let Some(path) = &config.my_account.vault {
+                return Err(Error::Vault(format!(
+                    "The account already uses the vault {}",
+                    path.display()
+                )));
+            }
+            validate_account(config)?;
+
+            let path = file
+                .clone()
+                .unwrap_or_else(|| config.state_directory().join(VAULT_FILE));
+            let path = std::path::absolute(&path)?;
+            if path.exists() {
+                return Err(Error::Vault(format!("{} already exists", path.display())));
+            }
+
+            let mut vault = Vault::create(&path, &new_vault_passphrase(true)?)?;
+            vault.set(TOKEN_KEY, config.my_account.authorization_token.as_str());
+            config.vault = Some(vault);
+            config.save_device_cache()?;
+
+            // Remove the secrets from the config file and the device ID cache
+            let profile = config.profile.clone();
+            let profile = profile.as_deref();
+            let mut editor = ConfigEditor::open(config_path)?;
+            editor.remove(profile, "my_account", "authorization_token")?;
+            editor.set(profile, "my_account", "vault", path.display().to_string())?;
+            editor.remove(profile, "cache", "device_id")?;
+            editor.remove(profile, "cache", "device_id_timestamp")?;
+            editor.save()?;
+
+            let device_cache = config.device_cache_path();
+            if device_cache.exists() {
+                std::fs::remove_file(device_cache)?;
+            }
+
+            print_success(&format!(
+                "Token and device ID moved to the vault {}",
+                path.display()
+            ));
+        }
+        VaultCommand::Passwd => {
+            let Some(mut vault) = config.vault.take() else {
+                return Err(Error::Vault(
+                    "The account doesn't use a vault (see `vault init`)".to_string(),
+                ));
+            };
+            vault.set_passphrase(&new_vault_passphrase(false)?)?;
+            vault.save()?;
+            print_success("Vault passphrase changed");
+        }
+    }
+
+    Ok(())
+}
+
+/// Run every configuration and environment check and print the report.
+async fn run_doctor(config: &Config, offline: bool) -> Result<()> {
+    let mut checks = check_config(config);
+    checks.extend(check_environment(config));
+    if !offline {
+        checks.extend(check_connectivity(config).await);
+    }
+    print_checks(&checks);
+
+    let failed = checks.iter().filter(|c| c.status == Status::Fail).count();
+    if failed > 0 {
+        return Err(Error::Config(format!("{} check(s) failed", failed)));
+    }
+    Ok(())
+}
+
+/// Get the vault passphrase from the environment, or ask for it.
+fn vault_passphrase() -> Result<String> {
+    match std::env::var(PASSPHRASE_ENV) {
+        Ok(passphrase) => Ok(passphrase),
+        Err(_) => read_passphrase("Vault passphrase: ", false, PASSPHRASE_ENV),
+    }
+}
+
+/// Get the passphrase for a new vault or a passphrase change.
+///
+/// Read from `FANSLY_VAULT_NEW_PASSPHRASE`, else (for a new vault, which has
+/// no current passphrase) from `FANSLY_VAULT_PASSPHRASE`, else asked for.
+fn new_vault_passphrase(creating: bool) -> Result<String> {
+    let mut from_env = std::env::var(NEW_PASSPHRASE_ENV);
+    if creating {
+        from_env = from_env.or_else(|_| std::env::var(PASSPHRASE_ENV));
+    }
+    match from_env {
+        Ok(passphrase) => Ok(passphrase),
+        Err(_) => read_passphrase("New vault passphrase: ", true, NEW_PASSPHRASE_ENV),
+    }
+}
+
+/// Ask for a passphrase on the terminal without echoing it.
+fn read_passphrase(prompt: &str, confirm: bool, env: &str) -> Result<String> {
+    let term = Term::stderr();
+    if !term.is_term() {
+        return Err(Error::Vault(format!(
+            "No terminal to ask for the passphrase; set {}",
+            env
+        )));
+    }
+
+    term.write_str(prompt)?;
+    let passphrase = term.read_secure_line()?;
+    if confirm {
+        term.write_str("Repeat passphrase: ")?;
+        if term.read_secure_line()? != passphrase {
+            return Err(Error::Vault("Passphrases don't match".to_string()));
+        }
+    }
+    Ok(passphrase)
+}
+
+/// Store the current device ID in the device ID cache if it changed.
+async fn save_device_id(api: &FanslyApi, config: &mut Config) -> Result<()> {
+    let device_id = api.get_device_id().await?;
+    let Some(timestamp) = api.get_device_id_timestamp().await else {
+        return Ok(());
+    };
+
+    if config.cache.device_id.as_deref() == Some(device_id.as_str())
+        && config.cache.device_id_timestamp == Some(timestamp)
+    {
+        return Ok(());
+    }
+
+    config.update_cache(device_id, timestamp)
+}
+
+/// Print the effective configuration or the files it uses, or check that it is valid.
+fn run_config_command(config: &Config, config_path: &Path, action: ConfigCommand) -> Result<()> {
+    match action {
+        ConfigCommand::Path => {
+            let status = if config_path.exists() {
+                ""
+            } else {
+                " (not found)"
+            };
+            let paths = [
+                ("Config file", config_path.display().to_string() + status),
+                (
+                    "Device ID cache",
+                    config.device_cache_path().display().to_string(),
+                ),
+                (
+                    "State directory",
+                    config.state_directory().display().to_string(),
+                ),
+                (
+                    "Download directory",
+                    config.download_directory().display().to_string(),
+                ),
+                (
+                    "Daemon status",
+                    config.daemon_status_path().display().to_string(),
+                ),
+            ];
+            for (name, path) in paths {
+                println!("{:<20}{}", format!("{}:", name), path);
+            }
+        }
+        ConfigCommand::Show => {
+            let mut shown = config.clone();
+            shown.my_account.authorization_token =
+                mask_secret(&shown.my_account.authorization_token);
+            for profile in shown.profiles.values_mut() {
+                profile.my_account.authorization_token =
+                    mask_secret(&profile.my_account.authorization_token);
+            }
+            let content = toml::to_string_pretty(&shown)
+                .map_err(|e| Error::Config(format!("Failed to serialize config: {}", e)))?;
+            println!("{}", content);
+        }
+        ConfigCommand::Validate => {
+            validate_config(config)?;
+            if config.options.download_mode == DownloadMode::Single {
+                config.single_post_ids()?;
+            }
+            print_success("Configuration is valid");
+        }
+    }
+
+    Ok(())
+}
+
+/// Hide all but the last few characters of a secret.
+fn mask_secret(secret: &str) -> String {
+    let visible = secret.len().saturating_sub(4).max(secret.len() / 2);
+    match secret.get(visible..) {
+        Some(tail) if !secret.is_empty() => format!("****{}", tail),
+        _ => String::new(),
+    }
+}
+
+/// Get the downloaded creator folders to scan: those of the targeted creators,
+/// or every creator folder in the download directory if none are targeted.
+fn creator_folders(config: &Config) -> Result<Vec<(String, std::path::PathBuf)>> {
+    let usernames = config.targeted_creator.usernames();
+    if !usernames.is_empty() {
+        let registry = CreatorRegistry::load_for(config)?;
+        return usernames
+            .iter()
+            .map(|name| {
+                let config = &config.for_creator(name);
+                let folder = match registry.id_for_username(name) {
+                    Some(id) => get_creator_folder_for(config, name, id)?,
+                    None => get_creator_folder(config, name)?,
+                };
+                Ok((name.clone(), folder))
+            })
+            .collect();
+    }
+
+    let base_dir = config.download_directory();
+    if !base_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    // Username aliases are symlinks and would be counted twice
+    let mut folders = Vec::new();
+    for entry in std::fs::read_dir(&base_dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if entry.file_type()?.is_dir() && !name.starts_with('.') {
+            folders.push((name, entry.path()));
+        }
+    }
+    folders.sort();
+    Ok(folders)
+}
+
+/// Check the downloaded files of each creator folder, optionally deleting broken ones.
+fn verify_downloads(config: &Config, delete: bool) -> Result<()> {
+    let mut checked = 0;
+    let mut broken_count = 0;
+
+    for (name, folder) in creator_folders(config)? {
+        let (count, broken) = verify_directory(&folder)?;
+        checked += count;
+        broken_count += broken.len();
+        print_info(&format!(
+            "{}: {} file(s) checked, {} broken",
+            name,
+            count,
+            broken.len()
+        ));
+
+        for file in broken {
+            print_warning(&format!("{}: {}", file.path.display(), file.reason));
+            if delete {
+                std::fs::remove_file(&file.path)?;
+            }
+        }
+    }
+
+    if broken_count == 0 {
+        print_success(&format!("All {} file(s) are intact", checked));
+    } else if delete {
+        print_success(&format!(
+            "Deleted {} broken file(s), they will be downloaded again on the next run",
+            broken_count
+        ));
+    } else {
+        return Err(Error::Media(format!(
+            "{} of {} file(s) are broken (use --delete to remove them)",
+            broken_count, checked
+        )));
+    }
+
+    Ok(())
+}
+
+/// Print file counts and sizes of each creator folder.
+fn show_download_stats(config: &Config) -> Result<()> {
+    let mut total = FolderStats::default();
+
+    for (name, folder) in creator_folders(config)? {
+        let stats = folder_stats(&folder)?;
+        print_folder_stats(&name, &stats);
+        total.add(&stats);
+    }
+
+    print_folder_stats("all creators", &total);
+    Ok(())
+}
+
+/// Print the account ID and folder of each targeted creator.
+async fn list_creators(
+    api: &FanslyApi,
+    config: &Config,
+    registry: &CreatorRegistry,
+    creators: &[String],
+) -> Result<()> {
+    for (creator_name, creator_info) in resolve_creators(api, registry, creators).await? {
+        match creator_info {
+            Some(account) => {
+                let config = &config.for_creator(&creator_name);
+                let folder = get_creator_folder_for(config, &account.username, &account.id)?;
+                println!("{}  {}  {}", account.username, account.id, folder.display());
+            }
+            None => print_warning(&format!("Creator not found: {}", creator_name)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Download the media that failed in previous runs again.
+///
+/// Media is looked up again for fresh download URLs; media that is no longer
+/// available is dropped from the list.
+async fn retry_failed(
+    api: &FanslyApi,
+    config: &Config,
+    global_state: &mut GlobalState,
+) -> Result<()> {
+    let mut failures = FailedDownloads::load_for(config)?;
+    if failures.is_empty() {
+        print_info("No failed downloads to retry");
+        return Ok(());
+    }
+    print_info(&format!(
+        "Retrying {} failed download(s)...",
+        failures.len()
+    ));
+
+    for (creator_id, entries) in failures.by_creator() {
+        let creator_name = entries[0].creator_name.clone();
+        let creator_config = config.for_creator(&creator_name);
+        let mut state = DownloadState::new(creator_name.clone(), creator_id);
+
+        let media_ids: Vec<String> = entries.iter().map(|e| e.media_id.clone()).collect();
+        let mut media = Vec::new();
+        for chunk in media_ids.chunks(BATCH_SIZE) {
+            media.extend(api.get_media_info(chunk).await?);
+        }
+
+        for entry in &entries {
+            let item = media.iter().find(|m| m.id == entry.media_id).and_then(|m| {
+                parse_media_info_capped(
+                    m,
+                    creator_config.options.download_media_previews,
+                    creator_config.options.max_height,
+                )
+            });
+            let Some(item) = item else {
+                print_warning(&format!(
+                    "Media {} of {} is no longer available",
+                    entry.media_id, creator_name
+                ));
+                failures.remove(&entry.media_id);
+                continue;
+            };
+
+            // Failures are recorded in the state again
+            failures.remove(&entry.media_id);
+            if let Err(e) =
+                download_media_item(api, &creator_config, &mut state, &item, &entry.target_dir)
+                    .await
+            {
+                print_error(&format!("Failed to download {}: {}", entry.media_id, e));
+            }
+        }
+
+        for failure in state.failures.drain(..) {
+            let attempts = entries
+                .iter()
+                .find(|e| e.media_id == failure.media_id)
+                .map_or(0, |e| e.attempts);
+            failures.record(FailedDownload {
+                attempts: attempts + failure.attempts,
+                ..failure
+            });
+        }
+        failures.save()?;
+
+        print_creator_stats(&state);
+        global_state.add_creator_stats(&state);
+    }
+
+    if !failures.is_empty() {
+        print_warning(&format!(
+            "{} download(s) still failing, see {}",
+            failures.len(),
+            config.state_directory().join(FAILURES_FILE).display()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Serializes updates of the failed downloads file between creators.
+static FAILURES_LOCK: Mutex<()> = Mutex::new(());
+
+/// Add the failed downloads of a creator to the failed downloads file.
+fn save_failures(config: &Config, state: &DownloadState) {
+    if state.failures.is_empty() {
+        return;
+    }
+
+    let _guard = FAILURES_LOCK.lock().unwrap();
+    let result = FailedDownloads::load_for(config).and_then(|mut failures| {
+        for failure in &state.failures {
+            failures.record(failure.clone());
+        }
+        failures.save()
+    });
+
+    match result {
+        Ok(()) => print_warning(&format!(
+            "{} download(s) failed, run retry-failed to try again",
+            state.failures.len()
+        )),
+        Err(e) => print_warning(&format!("Failed to save failed downloads: {}", e)),
+    }
+}
+
+/// Print the timeline walls of each creator.
+async fn list_creator_walls(
+    api: &FanslyApi,
+    registry: &CreatorRegistry,
+    creators: &[String],
+) -> Result<()> {
+    for (creator_name, creator_info) in resolve_creators(api, registry, creators).await? {
+        let creator_info =
+            creator_info.ok_or_else(|| Error::AccountNotFound(creator_name.clone()))?;
+        let walls = api.get_creator_walls(&creator_info.id).await?;
+
+        print_info(&format!("Walls for {} ({}):", creator_name, walls.len()));
+        for wall in &walls {
+            println!("  {}  {}", wall.id, wall.display_name());
+        }
+    }
+
+    Ok(())
+}
+
+/// Interval between status file heartbeats while the daemon is idle.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Sync the targeted creators on a schedule until SIGTERM or Ctrl+C.
+///
+/// One API client (and WebSocket session) is kept for the whole run. On shutdown,
+/// files being downloaded are finished but no new ones are started.
+async fn run_daemon(
+    api: &FanslyApi,
+    config: &mut Config,
+    mut registry: CreatorRegistry,
+    creators: &[String],
+) -> Result<()> {
+    if creators.is_empty() {
+        return Err(Error::MissingConfig(
+            "targeted_creator.usernames".to_string(),
+        ));
+    }
+
+    let modes: Vec<(String, DownloadMode)> = creators
+        .iter()
+        .map(|name| (name.clone(), config.for_creator(name).options.download_mode))
+        .collect();
+    let mut schedule = Schedule::from_config(&config.daemon, &modes, Instant::now())?;
+    let status_path = config.daemon_status_path();
+    let mut status = DaemonStatus::default();
+    spawn_shutdown_handler();
+
+    print_info(&format!(
+        "Daemon started for {} creator(s), status file: {}",
+        creators.len(),
+        status_path.display()
+    ));
+
+    while !is_shutdown_requested() {
+        let now = Instant::now();
+        let due = schedule.due(now);
+
+        if due.is_empty() {
+            let next = schedule.next_run().unwrap_or(now + HEARTBEAT_INTERVAL);
+            status.state = DaemonState::Idle;
+            status.next_run = Some(wall_clock(next));
+            write_status(&mut status, &status_path);
+
+            tokio::select! {
+                _ = tokio::time::sleep_until(next.min(now + HEARTBEAT_INTERVAL).into()) => {}
+                _ = shutdown_requested() => {}
+            }
+            continue;
+        }
+
+        status.state = DaemonState::Running;
+        status.cycles += 1;
+        write_status(&mut status, &status_path);
+
+        let names: Vec<String> = due.iter().map(|(name, _)| name.clone()).collect();
+        let accounts = match resolve_creators(api, &registry, &names).await {
+            Ok(accounts) => accounts,
+            Err(e) => {
+                print_error(&format!("Failed to look up creators: {}", e));
+                names.iter().map(|name| (name.clone(), None)).collect()
+            }
+        };
+
+        let mut jobs = Vec::new();
+        for ((name, account), (_, sources)) in accounts.into_iter().zip(due) {
+            match account {
+                Some(account) => jobs.push((name, account, sources)),
+                None => {
+                    for source in sources {
+                        let next = schedule.reschedule(&name, source, Instant::now());
+                        let error = format!("Creator not found: {}", name);
+                        status.record(&name, source, Err(error), wall_clock(next));
+                    }
+                }
+            }
+        }
+
+        // Creators are synced in parallel, sharing the registry
+        let shared = Mutex::new(registry);
+        let cycle_config: &Config = config;
+        let results = stream::iter(&jobs)
+            .map(|(name, account, sources)| {
+                let span = tracing::info_span!("creator", name = %account.username);
+                let (config, shared) = (cycle_config.for_creator(name), &shared);
+                async move {
+                    let results = sync_creator(api, &config, shared, account, sources).await;
+                    (name, sources, results)
+                }
+                .instrument(span)
+            })
+            .buffer_unordered(cycle_config.options.max_concurrent_creators)
+            .collect::<Vec<_>>()
+            .await;
+        registry = shared.into_inner().unwrap();
+
+        for (name, sources, result) in results {
+            let outcomes: Vec<(DownloadType, std::result::Result<u64, String>)> = match result {
+                Ok(outcomes) => outcomes
+                    .into_iter()
+                    .map(|(source, outcome)| (source, outcome.map_err(|e| e.to_string())))
+                    .collect(),
+                Err(e) => sources.iter().map(|&s| (s, Err(e.to_string()))).collect(),
+            };
+
+            for (source, outcome) in outcomes {
+                if let Err(e) = &outcome {
+                    print_error(&format!("Failed to sync {} {}: {}", name, source, e));
+                }
+                let next = schedule.reschedule(name, source, Instant::now());
+                status.record(name, source, outcome, wall_clock(next));
+            }
+        }
+
+        // The device ID may have been refreshed during the cycle
+        if let Err(e) = save_device_id(api, config).await {
+            print_warning(&format!("Failed to save device ID: {}", e));
+        }
+    }
+
+    status.state = DaemonState::Stopped;
+    status.next_run = None;
+    write_status(&mut status, &status_path);
+    print_info("Daemon stopped");
+
+    Ok(())
+}
+
+/// Request a graceful shutdown on SIGTERM or Ctrl+C.
+fn spawn_shutdown_handler() {
+    tokio::spawn(async {
+        #[cfg(unix)]
+        let terminate = async {
+            match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+                Ok(mut signal) => {
+                    signal.recv().await;
+                }
+                Err(_) => std::future::pending().await,
+            }
+        };
+        #[cfg(not(unix))]
+        let terminate = std::future::pending::<()>();
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate => {}
+        }
+
+        print_warning("Shutting down, finishing downloads in progress...");
+        request_shutdown();
+    });
+}
+
+/// Write the daemon status file, warning on failure.
+fn write_status(status: &mut DaemonStatus, path: &Path) {
+    if let Err(e) = status.write(path) {
+        print_warning(&format!("Failed to write status file: {}", e));
+    }
+}
+
+/// Sync the given sources of one creator, returning the number of files
+/// downloaded (or the error) per source.
+async fn sync_creator(
+    api: &FanslyApi,
+    config: &Config,
+    registry: &Mutex<CreatorRegistry>,
+    account: &AccountInfo,
+    sources: &[DownloadType],
+) -> Result<Vec<(DownloadType, Result<u64>)>> {
+    let folder = {
+        let mut registry = registry.lock().unwrap();
+        prepare_creator_folder(config, &mut registry, account)?
+    };
+
+    let new_state = || {
+        let mut state = DownloadState::new(account.username.clone(), account.id.clone());
+        state.following = account.following.unwrap_or(false);
+        state.subscribed = account.subscribed.unwrap_or(false);
+        state.base_path = Some(folder.clone());
+        state
+    };
+
+    // The profile is backed up along with the timeline
+    if config.options.download_profile && sources.contains(&DownloadType::Timeline) {
+        if let Err(e) = backup_profile(api, config, &mut new_state(), account).await {
+            print_warning(&format!("Profile backup failed: {}", e));
+        }
+    }
+
+    let mut results = Vec::new();
+    for &source in sources {
+        if is_shutdown_requested() {
+            results.push((source, Err(Error::Interrupted)));
+            continue;
+        }
+
+        let mut state = new_state();
+        state.download_type = source;
+        let result = match source {
+            DownloadType::Timeline => download_timeline(api, config, &mut state).await,
+            DownloadType::Messages => download_messages(api, config, &mut state).await,
+            DownloadType::Collections => download_collections(api, config, &mut state).await,
+            DownloadType::Stories => download_stories(api, config, &mut state).await,
+            DownloadType::NotSet | DownloadType::Single => Ok(()),
+        };
+
+        if state.total_downloaded() > 0 {
+            print_creator_stats(&state);
+        }
+        save_failures(config, &state);
+        results.push((source, result.map(|()| state.total_downloaded())));
+    }
+
+    Ok(results)
+}
+
+/// Download new content of the targeted (and optionally followed) creators as
+/// it is announced, until Ctrl+C.
+async fn watch_creators(
+    api: &FanslyApi,
+    config: &Config,
+    registry: CreatorRegistry,
+    creators: &[String],
+    following: bool,
+) -> Result<()> {
+    if creators.is_empty() && !following {
+        return Err(Error::MissingConfig(
+            "targeted_creator.usernames".to_string(),
+        ));
+    }
+
+    // Subscribe before resolving so nothing announced meanwhile is missed
+    let mut events = api.subscribe_events();
+
+    let mut watched = HashMap::new();
+    for (creator_name, account) in resolve_creators(api, &registry, creators).await? {
+        match account {
+            Some(account) => {
+                watched.insert(
+                    account.id.clone(),
+                    (config.for_creator(&creator_name), account),
+                );
+            }
+            None => print_error(&format!("Creator not found: {}", creator_name)),
+        }
+    }
+    if watched.is_empty() && !following {
+        return Err(Error::Api("No creators to watch".into()));
+    }
+
+    if following {
+        print_info(&format!(
+            "Watching {} creator(s) and all followed creators for new content (Ctrl+C to stop)",
+            watched.len()
+        ));
+    } else {
+        print_info(&format!(
+            "Watching {} creator(s) for new content (Ctrl+C to stop)",
+            watched.len()
+        ));
+    }
+
+    // Accounts that announced something but aren't followed
+    let mut ignored = HashSet::new();
+
+    let registry = Mutex::new(registry);
+    loop {
+        let event = tokio::select! {
+            event = events.recv() => event,
+            _ = tokio::signal::ctrl_c() => break,
+        };
+
+        let event = match event {
+            Ok(event) => event,
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(missed)) => {
+                print_warning(&format!("Missed {} event(s) while busy", missed));
+                continue;
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                return Err(Error::Api("WebSocket event stream closed".into()));
+            }
+        };
+
+        let Some(account_id) = event.account_id() else {
+            continue;
+        };
+        if following && !watched.contains_key(account_id) && !ignored.contains(account_id) {
+            match api.get_accounts_by_ids(&[account_id.to_string()]).await {
+                Ok(accounts) => match accounts.into_iter().find(|a| a.following == Some(true)) {
+                    Some(account) => {
+                        print_info(&format!("Watching followed creator {}", account.username));
+                        watched.insert(
+                            account.id.clone(),
+                            (config.for_creator(&account.username), account),
+                        );
+                    }
+                    None => {
+                        ignored.insert(account_id.to_string());
+                    }
+                },
+                Err(e) => {
+                    print_warning(&format!("Failed to look up account {}: {}", account_id, e))
+                }
+            }
+        }
+
+        let Some((config, account)) = watched.get(account_id) else {
+            continue;
+        };
+
+        let span = tracing::info_span!("creator", name = %account.username);
+        match handle_watch_event(api, config, &registry, account, &event)
+            .instrument(span)
+            .await
+        {
+            Ok(state) => {
+                print_creator_stats(&state);
+                save_failures(config, &state);
+            }
+            Err(e) => print_error(&format!(
+                "Failed to download new content of {}: {}",
+                account.username, e
+            )),
+        }
+    }
+
+    print_info("Stopped watching");
+    Ok(())
+}
+
+/// Download the content announced by a single event.
+async fn handle_watch_event(
+    api: &FanslyApi,
+    config: &Config,
+    registry: &Mutex<CreatorRegistry>,
+    account: &AccountInfo,
+    event: &WsEvent,
+) -> Result<DownloadState> {
+    let mut state = DownloadState::new(account.username.clone(), account.id.clone());
+    state.base_path = Some({
+        let mut registry = registry.lock().unwrap();
+        prepare_creator_folder(config, &mut registry, account)?
+    });
+
+    match event {
+        WsEvent::NewPost { post_id, .. } => {
+            print_info(&format!("New post by {}: {}", account.username, post_id));
+            state.download_type = DownloadType::Timeline;
+            download_single_post(api, config, &mut state, post_id).await?;
+        }
+        WsEvent::NewMessage {
+            group_id,
+            message_id,
+            ..
+        } => {
+            print_info(&format!("New message from {}", account.username));
+            state.download_type = DownloadType::Messages;
+            download_message(api, config, &mut state, group_id, message_id).await?;
+        }
+        WsEvent::NewStory { .. } => {
+            print_info(&format!("New story by {}", account.username));
+            state.download_type = DownloadType::Stories;
+            download_stories(api, config, &mut state).await?;
+        }
+        WsEvent::Other { .. } => {}
+    }
+
+    Ok(state)
+}
+
+/// Download posts by ID, grouped by the creator that owns each post.
+async fn process_single_posts(
+    api: &FanslyApi,
+    config: &Config,
+    post_ids: &[String],
+    registry: &mut CreatorRegistry,
+    global_state: &mut GlobalState,
+) -> Result<()> {
+    print_info(&format!("Fetching {} post(s)...", post_ids.len()));
+    let groups = fetch_posts_by_creator(api, post_ids).await?;
+
+    for group in groups {
+        let creator_name = group.account.username.clone();
+        let config = &config.for_creator(&creator_name);
+        print_info(&format!("Processing creator: {}", creator_name));
+
+        let mut state = DownloadState::new(creator_name.clone(), group.account.id.clone());
+        state.download_type = DownloadType::Single;
+
+        let result = match prepare_creator_folder(config, registry, &group.account) {
+            Ok(folder) => {
+                state.base_path = Some(folder);
+                download_posts(api, config, &mut state, &group.posts).await
+            }
+            Err(e) => Err(e),
+        };
+
+        save_failures(config, &state);
+        match result {
+            Ok(()) => {
+                print_creator_stats(&state);
+                global_state.add_creator_stats(&state);
+            }
+            Err(e) => {
+                print_error(&format!("Failed to process {}: {}", creator_name, e));
+                global_state.mark_creator_failed();
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Record a creator in the registry, handle renames, and return its folder.
+fn prepare_creator_folder(
+    config: &Config,
+    registry: &mut CreatorRegistry,
+    account: &AccountInfo,
+) -> Result<std::path::PathBuf> {
+    let previous = registry.record(&account.id, &account.username);
+    registry.save()?;
+
+    if let Some(previous) = &previous {
+        print_info(&format!(
+            "Creator {} was renamed to {}",
+            previous, account.username
+        ));
+        migrate_renamed_creator(config, previous, &account.username)?;
+    }
+
+    ensure_username_alias(config, &account.username, &account.id, previous.as_deref())?;
+
+    get_creator_folder_for(config, &account.username, &account.id)
+}
+
 /// Process a single creator.
 async fn process_creator(
     api: &FanslyApi,
     config: &Config,
-    creator_name: &str,
+    registry: &Mutex<CreatorRegistry>,
+    creator_info: &AccountInfo,
 ) -> Result<DownloadState> {
-    // Get creator account info
-    let creator_info = api.get_creator_account_info(creator_name).await?;
+    // Set base path (with path traversal protection)
+    let creator_folder = {
+        let mut registry = registry.lock().unwrap();
+        prepare_creator_folder(config, &mut registry, creator_info)?
+    };
 
-    // Initialize download state
-    let mut state = DownloadState::new(creator_name.to_string(), creator_info.id.clone());
-    state.following = creator_info.following.unwrap_or(false);
-    state.subscribed = creator_info.subscribed.unwrap_or(false);
+    // Initialize download state with the current username
+    let new_state = || {
+        let mut state = DownloadState::new(creator_info.username.clone(), creator_info.id.clone());
+        state.following = creator_info.following.unwrap_or(false);
+        state.subscribed = creator_info.subscribed.unwrap_or(false);
+        state.base_path = Some(creator_folder.clone());
+        state
+    };
+    let mut state = new_state();
 
-    // Set base path (with path traversal protection)
-    state.base_path = Some(get_creator_folder(config, creator_name)?);
+    // Back up profile before downloading content (in its own state, so the
+    // avatar and banner don't count towards the creator's statistics)
+    if config.options.download_profile {
+        if let Err(e) = backup_profile(api, config, &mut new_state(), creator_info).await {
+            print_warning(&format!("Profile backup failed: {}", e));
+        }
+    }
 
     // Execute based on download mode
     match config.options.download_mode {
         DownloadMode::Normal => {
-            // Download timeline, messages, and collections
+            // Download timeline, messages, collections, and stories
             state.download_type = DownloadType::Timeline;
             download_timeline(api, config, &mut state).await?;
 
@@ -181,6 +1264,11 @@ async fn process_creator(
             if let Err(e) = download_collections(api, config, &mut state).await {
                 print_warning(&format!("Collections download failed: {}", e));
             }
+
+            state.download_type = DownloadType::Stories;
+            if let Err(e) = download_stories(api, config, &mut state).await {
+                print_warning(&format!("Stories download failed: {}", e));
+            }
         }
         DownloadMode::Timeline => {
             state.download_type = DownloadType::Timeline;
@@ -191,19 +1279,16 @@ async fn process_creator(
             download_messages(api, config, &mut state).await?;
         }
         DownloadMode::Single => {
-            state.download_type = DownloadType::Single;
-            let post_id = config
-                .options
-                .single_post_id
-                .as_ref()
-                .ok_or_else(|| Error::Config("Post ID required for single mode".into()))?;
-            let post_id = parse_post_id(post_id)?;
-            download_single_post(api, config, &mut state, &post_id).await?;
+            unreachable!("single post mode is handled by process_single_posts")
         }
         DownloadMode::Collection => {
             state.download_type = DownloadType::Collections;
             download_collections(api, config, &mut state).await?;
         }
+        DownloadMode::Stories => {
+            state.download_type = DownloadType::Stories;
+            download_stories(api, config, &mut state).await?;
+        }
     }
 
     Ok(state)
@@ -4,4 +4,6 @@ pub mod item;
 pub mod parser;
 
 pub use item::{MediaItem, MediaType};
-pub use parser::{extract_media_ids, parse_media_info};
+pub use parser::{
+    extract_media_ids, parse_media_details, parse_media_info, parse_media_info_capped,
+};
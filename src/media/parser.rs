@@ -10,6 +10,16 @@ type VariantInfo = (String, String, u32, u32, HashMap<String, String>);
 
 /// Parse an AccountMedia into a MediaItem, selecting the best resolution.
 pub fn parse_media_info(media: &AccountMedia, include_previews: bool) -> Option<MediaItem> {
+    parse_media_info_capped(media, include_previews, None)
+}
+
+/// Parse an AccountMedia into a MediaItem, selecting the best resolution at
+/// most `max_height` pixels high (or the smallest one if none fits).
+pub fn parse_media_info_capped(
+    media: &AccountMedia,
+    include_previews: bool,
+    max_height: Option<u32>,
+) -> Option<MediaItem> {
     // Skip if no access and not handling previews
     if !media.access && media.preview.is_none() {
         return None;
@@ -26,14 +36,34 @@ pub fn parse_media_info(media: &AccountMedia, include_previews: bool) -> Option<
         return None;
     };
 
+    let mut item = parse_details(media_details, is_preview, max_height)?;
+    item.media_id = media.id.clone();
+    Some(item)
+}
+
+/// Parse standalone media details (e.g. an avatar or banner) into a MediaItem.
+///
+/// The media ID is taken from the details themselves.
+pub fn parse_media_details(media_details: &MediaDetails, is_preview: bool) -> Option<MediaItem> {
+    parse_details(media_details, is_preview, None)
+}
+
+fn parse_details(
+    media_details: &MediaDetails,
+    is_preview: bool,
+    max_height: Option<u32>,
+) -> Option<MediaItem> {
     // Find the best resolution variant
-    let (url, mimetype, width, height, metadata) = select_best_variant(media_details)?;
+    let (url, mimetype, width, height, metadata) = match max_height {
+        Some(max_height) => select_capped_variant(media_details, max_height),
+        None => select_best_variant(media_details),
+    }?;
 
     // Determine file extension from URL
     let extension = extract_extension(&url, &mimetype);
 
     Some(MediaItem {
-        media_id: media.id.clone(),
+        media_id: media_details.id.clone(),
         created_at: media_details.created_at,
         mimetype,
         download_url: url,
@@ -87,6 +117,51 @@ fn select_best_variant(details: &MediaDetails) -> Option<VariantInfo> {
     best_url.map(|url| (url, best_mimetype, best_width, best_height, best_metadata))
 }
 
+/// Select the highest resolution variant at most `max_height` pixels high.
+///
+/// Falls back to the smallest variant if none fits, and to the best one if
+/// heights are unknown.
+fn select_capped_variant(details: &MediaDetails, max_height: u32) -> Option<VariantInfo> {
+    let mut candidates: Vec<VariantInfo> = Vec::new();
+
+    if let Some(loc) = details.locations.first() {
+        candidates.push((
+            loc.location.clone(),
+            details.mimetype.clone(),
+            details.width.unwrap_or(0),
+            details.height.unwrap_or(0),
+            loc.metadata.clone(),
+        ));
+    }
+
+    for variant in &details.variants {
+        if !is_compatible_mimetype(&details.mimetype, &variant.mimetype) {
+            continue;
+        }
+        if let Some(loc) = variant.locations.first() {
+            candidates.push((
+                loc.location.clone(),
+                variant.mimetype.clone(),
+                variant.width.unwrap_or(0),
+                variant.height.unwrap_or(0),
+                loc.metadata.clone(),
+            ));
+        }
+    }
+
+    let resolution = |c: &VariantInfo| (c.2 as u64) * (c.3 as u64);
+    let (fitting, too_large): (Vec<_>, Vec<_>) = candidates
+        .into_iter()
+        .filter(|c| c.3 > 0)
+        .partition(|c| c.3 <= max_height);
+
+    fitting
+        .into_iter()
+        .max_by_key(resolution)
+        .or_else(|| too_large.into_iter().min_by_key(resolution))
+        .or_else(|| select_best_variant(details))
+}
+
 /// Check if two MIME types are compatible (same base type).
 fn is_compatible_mimetype(base: &str, variant: &str) -> bool {
     let base_type = base.split('/').next().unwrap_or("");
@@ -180,6 +255,46 @@ pub fn extract_media_ids(
 mod tests {
     use super::*;
 
+    fn details(height: u32, variants: &[u32]) -> MediaDetails {
+        let location = |name: String| crate::api::types::MediaLocation {
+            location: format!("https://cdn.example.com/{}.mp4", name),
+            metadata: HashMap::new(),
+        };
+        MediaDetails {
+            id: "1".to_string(),
+            created_at: 0,
+            mimetype: "video/mp4".to_string(),
+            width: Some(height * 16 / 9),
+            height: Some(height),
+            locations: vec![location(height.to_string())],
+            variants: variants
+                .iter()
+                .map(|&h| crate::api::types::MediaVariant {
+                    id: h.to_string(),
+                    mimetype: "video/mp4".to_string(),
+                    width: Some(h * 16 / 9),
+                    height: Some(h),
+                    locations: vec![location(h.to_string())],
+                    updated_at: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_max_height_cap() {
+        let media = details(2160, &[1080, 720]);
+        assert_eq!(parse_details(&media, false, None).unwrap().height, 2160);
+        assert_eq!(
+            parse_details(&media, false, Some(1080)).unwrap().height,
+            1080
+        );
+        assert_eq!(parse_details(&media, false, Some(900)).unwrap().height, 720);
+
+        // Nothing fits: take the smallest
+        assert_eq!(parse_details(&media, false, Some(480)).unwrap().height, 720);
+    }
+
     #[test]
     fn test_extract_extension_from_url() {
         assert_eq!(
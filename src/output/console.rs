@@ -2,29 +2,56 @@
 
 use console::style;
 
+use crate::doctor::{Check, Status};
+use crate::output::progress::suspend;
+
 /// Print an info message.
 pub fn print_info(message: &str) {
-    println!("{} {}", style("INFO").cyan().bold(), message);
+    suspend(|| println!("{} {}", style("INFO").cyan().bold(), message));
 }
 
 /// Print a success message.
 pub fn print_success(message: &str) {
-    println!("{} {}", style("OK").green().bold(), message);
+    suspend(|| println!("{} {}", style("OK").green().bold(), message));
 }
 
 /// Print a warning message.
 pub fn print_warning(message: &str) {
-    println!("{} {}", style("WARN").yellow().bold(), message);
+    suspend(|| println!("{} {}", style("WARN").yellow().bold(), message));
 }
 
 /// Print an error message.
 pub fn print_error(message: &str) {
-    eprintln!("{} {}", style("ERROR").red().bold(), message);
+    suspend(|| eprintln!("{} {}", style("ERROR").red().bold(), message));
 }
 
 /// Print a debug message.
 pub fn print_debug(message: &str) {
-    println!("{} {}", style("DEBUG").dim(), message);
+    suspend(|| println!("{} {}", style("DEBUG").dim(), message));
+}
+
+/// Print the results of the `doctor` checks with a summary line.
+pub fn print_checks(checks: &[Check]) {
+    for check in checks {
+        let label = match check.status {
+            Status::Pass => style("PASS").green().bold(),
+            Status::Warn => style("WARN").yellow().bold(),
+            Status::Fail => style("FAIL").red().bold(),
+        };
+        println!("{} {:<20}{}", label, check.name, check.message);
+        if let Some(hint) = &check.hint {
+            println!("     {:<20}{}", "", style(hint).dim());
+        }
+    }
+
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    println!();
+    println!(
+        "{} passed, {} warnings, {} failed",
+        count(Status::Pass),
+        count(Status::Warn),
+        count(Status::Fail)
+    );
 }
 
 /// Print the application banner.
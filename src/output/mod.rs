@@ -10,8 +10,13 @@ pub mod progress;
 pub mod stats;
 
 pub use console::{
-    print_banner, print_config_summary, print_debug, print_error, print_info, print_success,
-    print_warning,
+    print_banner, print_checks, print_config_summary, print_debug, print_error, print_info,
+    print_success, print_warning,
+};
+pub use progress::{
+    create_download_bar, create_item_bar, create_spinner, multi_progress, suspend, ProgressWriter,
+};
+pub use stats::{
+    print_creator_stats, print_folder_stats, print_global_stats, print_rate_limit_stats,
+    print_summary,
 };
-pub use progress::{create_download_bar, create_item_bar, create_spinner};
-pub use stats::{print_creator_stats, print_global_stats, print_summary};
@@ -1,10 +1,53 @@
 //! Progress bar utilities.
+//!
+//! All progress bars are drawn through one shared [`MultiProgress`], so bars of
+//! creators processed in parallel stack instead of overwriting each other.
+//! Console and log output goes through [`suspend`] to keep lines from being
+//! torn by a redraw.
 
-use indicatif::{ProgressBar, ProgressStyle};
+use std::io::{self, Write};
+use std::sync::OnceLock;
+
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Get the shared progress bar container.
+pub fn multi_progress() -> &'static MultiProgress {
+    static MULTI: OnceLock<MultiProgress> = OnceLock::new();
+    MULTI.get_or_init(MultiProgress::new)
+}
+
+/// Run a closure that writes to the console while progress bars are hidden.
+pub fn suspend<F: FnOnce() -> R, R>(f: F) -> R {
+    multi_progress().suspend(f)
+}
+
+/// Log writer that hides progress bars while a log line is written.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProgressWriter;
+
+impl Write for ProgressWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        suspend(|| io::stdout().lock().write_all(buf))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()
+    }
+}
+
+impl<'a> MakeWriter<'a> for ProgressWriter {
+    type Writer = ProgressWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        *self
+    }
+}
 
 /// Create a spinner for long-running operations.
 pub fn create_spinner(message: &str) -> ProgressBar {
-    let spinner = ProgressBar::new_spinner();
+    let spinner = multi_progress().add(ProgressBar::new_spinner());
     spinner.set_style(
         ProgressStyle::default_spinner()
             .template("{spinner:.green} {msg}")
@@ -17,7 +60,7 @@ pub fn create_spinner(message: &str) -> ProgressBar {
 
 /// Create a progress bar for downloads.
 pub fn create_download_bar(total: u64) -> ProgressBar {
-    let bar = ProgressBar::new(total);
+    let bar = multi_progress().add(ProgressBar::new(total));
     bar.set_style(
         ProgressStyle::default_bar()
             .template("{spinner:.green} [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
@@ -29,7 +72,7 @@ pub fn create_download_bar(total: u64) -> ProgressBar {
 
 /// Create a progress bar for item counts.
 pub fn create_item_bar(total: u64, message: &str) -> ProgressBar {
-    let bar = ProgressBar::new(total);
+    let bar = multi_progress().add(ProgressBar::new(total));
     bar.set_style(
         ProgressStyle::default_bar()
             .template(&format!(
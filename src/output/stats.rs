@@ -1,43 +1,83 @@
 //! Statistics reporting.
 
 use console::style;
+use indicatif::HumanBytes;
 
+use crate::api::RateLimitMetrics;
 use crate::download::{DownloadState, GlobalState};
+use crate::fs::FolderStats;
+use crate::output::progress::suspend;
 
 /// Print statistics for a single creator.
 pub fn print_creator_stats(state: &DownloadState) {
-    let creator_name = state.creator_name.as_deref().unwrap_or("unknown");
+    suspend(|| {
+        let creator_name = state.creator_name.as_deref().unwrap_or("unknown");
 
-    println!();
-    println!(
-        "{}",
-        style(format!("Statistics for {}:", creator_name)).bold()
-    );
-    println!("  Pictures: {}", state.pic_count);
-    println!("  Videos:   {}", state.vid_count);
-    println!("  Audio:    {}", state.audio_count);
-    println!("  Skipped:  {} (duplicates)", state.duplicate_count());
-    println!("  Total:    {} downloaded", state.total_downloaded());
+        println!();
+        println!(
+            "{}",
+            style(format!("Statistics for {}:", creator_name)).bold()
+        );
+        println!("  Pictures: {}", state.pic_count);
+        println!("  Videos:   {}", state.vid_count);
+        println!("  Audio:    {}", state.audio_count);
+        println!("  Skipped:  {} (duplicates)", state.duplicate_count());
+        println!("  Total:    {} downloaded", state.total_downloaded());
+    });
 }
 
 /// Print global statistics across all creators.
 pub fn print_global_stats(state: &GlobalState) {
-    println!();
-    println!("{}", style("═".repeat(50)).dim());
-    println!("{}", style("Global Statistics:").bold());
-    println!("  Creators processed: {}", state.creators_processed);
-    if state.creators_failed > 0 {
+    suspend(|| {
+        println!();
+        println!("{}", style("═".repeat(50)).dim());
+        println!("{}", style("Global Statistics:").bold());
+        println!("  Creators processed: {}", state.creators_processed);
+        if state.creators_failed > 0 {
+            println!(
+                "  Creators failed:    {}",
+                style(state.creators_failed).red()
+            );
+        }
+        println!("  Pictures: {}", state.pic_count);
+        println!("  Videos:   {}", state.vid_count);
+        println!("  Audio:    {}", state.audio_count);
+        println!("  Skipped:  {} (duplicates)", state.duplicate_count);
+        println!("  Total:    {} downloaded", state.total_downloaded());
+        println!("{}", style("═".repeat(50)).dim());
+    });
+}
+
+/// Print file counts and size of a downloaded folder.
+pub fn print_folder_stats(name: &str, stats: &FolderStats) {
+    suspend(|| {
+        println!();
+        println!("{}", style(format!("Files of {}:", name)).bold());
+        println!("  Pictures: {}", stats.pictures);
+        println!("  Videos:   {}", stats.videos);
+        println!("  Audio:    {}", stats.audio);
         println!(
-            "  Creators failed:    {}",
-            style(state.creators_failed).red()
+            "  Total:    {} ({})",
+            stats.total(),
+            HumanBytes(stats.bytes)
         );
-    }
-    println!("  Pictures: {}", state.pic_count);
-    println!("  Videos:   {}", state.vid_count);
-    println!("  Audio:    {}", state.audio_count);
-    println!("  Skipped:  {} (duplicates)", state.duplicate_count);
-    println!("  Total:    {} downloaded", state.total_downloaded());
-    println!("{}", style("═".repeat(50)).dim());
+    });
+}
+
+/// Print request counts and time spent waiting on the rate limiters.
+pub fn print_rate_limit_stats(api: &RateLimitMetrics, cdn: &RateLimitMetrics) {
+    suspend(|| {
+        println!("{}", style("Requests:").bold());
+        for (name, metrics) in [("API", api), ("CDN", cdn)] {
+            println!(
+                "  {}: {} ({} throttled, waited {:.1}s)",
+                name,
+                metrics.requests,
+                metrics.throttled,
+                metrics.waited.as_secs_f64()
+            );
+        }
+    });
 }
 
 /// Print a summary line for quick viewing.